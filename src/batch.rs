@@ -0,0 +1,169 @@
+//! Batched loading of embedded resources.
+//!
+//! When every item of a collection embeds a related resource (every order
+//! embeds its customer), loading each one separately costs one fetch per
+//! item. A `BatchEmbed` collects the key of the related resource from every
+//! item, de-duplicates the keys, calls one batch loader with all of them and
+//! embeds the results in the items.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use rustic_hal::batch::BatchEmbed;
+//! use rustic_hal::HalResource;
+//! use serde_json::json;
+//!
+//! let orders = vec![
+//!     HalResource::new(json!({"id": 1, "customer_id": 7})),
+//!     HalResource::new(json!({"id": 2, "customer_id": 7})),
+//! ];
+//! let customers = BatchEmbed::new("customer", |order: &HalResource| {
+//!     order.get_extra_data::<u64>("customer_id").ok()
+//! });
+//! let orders = customers
+//!     .load(orders, |ids: Vec<u64>| {
+//!         assert_eq!(ids, vec![7]);
+//!         Ok(ids
+//!             .into_iter()
+//!             .map(|id| (id, HalResource::new(json!({ "id": id }))))
+//!             .collect::<HashMap<_, _>>())
+//!     })
+//!     .unwrap();
+//!
+//! assert!(orders[1].get_resource("customer").is_some());
+//! ```
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::hash::Hash;
+
+use super::embed::{embed, Embedded};
+use super::resource::HalResource;
+use super::HalResult;
+
+/// Embeds one relation in many resources with a single batch load.
+pub struct BatchEmbed<F> {
+    rel: String,
+    key_of: F,
+}
+
+impl<F> BatchEmbed<F> {
+    /// Embed under `rel` the resource keyed by `key_of(item)`; items for
+    /// which `key_of` returns `None` are left alone.
+    pub fn new(rel: &str, key_of: F) -> BatchEmbed<F> {
+        BatchEmbed {
+            rel: rel.to_string(),
+            key_of,
+        }
+    }
+
+    /// The distinct keys of `items`, in order of first appearance.
+    pub fn keys<K>(&self, items: &[HalResource]) -> Vec<K>
+    where
+        F: Fn(&HalResource) -> Option<K>,
+        K: Eq + Hash + Clone,
+    {
+        let mut seen = HashSet::new();
+        items
+            .iter()
+            .filter_map(|item| (self.key_of)(item))
+            .filter(|key| seen.insert(key.clone()))
+            .collect()
+    }
+
+    fn attach<K, V>(&self, items: Vec<HalResource>, loaded: HashMap<K, V>) -> Vec<HalResource>
+    where
+        F: Fn(&HalResource) -> Option<K>,
+        K: Eq + Hash,
+        V: Into<Embedded> + Clone,
+    {
+        items
+            .into_iter()
+            .map(|item| match (self.key_of)(&item).and_then(|k| loaded.get(&k)) {
+                Some(value) => embed(item, &self.rel, value.clone().into()),
+                None => item,
+            })
+            .collect()
+    }
+
+    /// Load the related resources of `items` with one call to `loader`.
+    ///
+    /// Keys missing from the loader's result are not embedded.
+    pub fn load<K, V, L>(&self, items: Vec<HalResource>, loader: L) -> HalResult<Vec<HalResource>>
+    where
+        F: Fn(&HalResource) -> Option<K>,
+        K: Eq + Hash + Clone,
+        V: Into<Embedded> + Clone,
+        L: FnOnce(Vec<K>) -> HalResult<HashMap<K, V>>,
+    {
+        let keys = self.keys(&items);
+        if keys.is_empty() {
+            return Ok(items);
+        }
+        let loaded = loader(keys)?;
+        Ok(self.attach(items, loaded))
+    }
+
+    /// Same as `load`, with an asynchronous loader.
+    pub async fn load_async<K, V, L, Fut>(
+        &self,
+        items: Vec<HalResource>,
+        loader: L,
+    ) -> HalResult<Vec<HalResource>>
+    where
+        F: Fn(&HalResource) -> Option<K>,
+        K: Eq + Hash + Clone,
+        V: Into<Embedded> + Clone,
+        L: FnOnce(Vec<K>) -> Fut,
+        Fut: Future<Output = HalResult<HashMap<K, V>>>,
+    {
+        let keys = self.keys(&items);
+        if keys.is_empty() {
+            return Ok(items);
+        }
+        let loaded = loader(keys).await?;
+        Ok(self.attach(items, loaded))
+    }
+
+    /// Same as `load`, for the resources embedded under `items_rel` in
+    /// `collection`.
+    pub fn load_embedded<K, V, L>(
+        &self,
+        mut collection: HalResource,
+        items_rel: &str,
+        loader: L,
+    ) -> HalResult<HalResource>
+    where
+        F: Fn(&HalResource) -> Option<K>,
+        K: Eq + Hash + Clone,
+        V: Into<Embedded> + Clone,
+        L: FnOnce(Vec<K>) -> HalResult<HashMap<K, V>>,
+    {
+        if let Some(items) = collection.get_resources_mut(items_rel) {
+            let loaded = self.load(std::mem::take(items), loader)?;
+            *items = loaded;
+        }
+        Ok(collection)
+    }
+
+    /// Same as `load_embedded`, with an asynchronous loader.
+    pub async fn load_embedded_async<K, V, L, Fut>(
+        &self,
+        mut collection: HalResource,
+        items_rel: &str,
+        loader: L,
+    ) -> HalResult<HalResource>
+    where
+        F: Fn(&HalResource) -> Option<K>,
+        K: Eq + Hash + Clone,
+        V: Into<Embedded> + Clone,
+        L: FnOnce(Vec<K>) -> Fut,
+        Fut: Future<Output = HalResult<HashMap<K, V>>>,
+    {
+        if let Some(items) = collection.get_resources_mut(items_rel) {
+            let loaded = self.load_async(std::mem::take(items), loader).await?;
+            *items = loaded;
+        }
+        Ok(collection)
+    }
+}
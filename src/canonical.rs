@@ -0,0 +1,139 @@
+//! Canonical serialization and entity tags.
+//!
+//! The canonical form of a resource is its JSON serialization with:
+//!
+//! * object keys sorted everywhere, the payload included;
+//! * a relation holding a single link written as an object, and several
+//!   links (or any `curies`) written as an array, whatever form was used to
+//!   build or parse the resource;
+//! * no `"templated": false` on links, as it is the default;
+//! * no insignificant whitespace.
+//!
+//! Two resources with the same canonical form carry the same representation,
+//! so its SHA-256 digest makes a weak `ETag`: weak, since the bytes actually
+//! sent may still differ, for instance in the form of a relation.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::HalResource;
+//! use serde_json::json;
+//!
+//! let a = HalResource::new(json!({"b": 1, "a": {"d": 2, "c": 3}})).with_link("self", "/x");
+//! assert_eq!(
+//!     a.to_canonical_string().unwrap(),
+//!     r#"{"_links":{"self":{"href":"/x"}},"a":{"c":3,"d":2},"b":1}"#
+//! );
+//! assert!(a.etag().unwrap().starts_with("W/\""));
+//! ```
+use serde_json::{to_string, Value as JsonValue};
+use sha2::{Digest, Sha256};
+
+use super::HalResult;
+
+/// Write `value` with sorted keys and no whitespace.
+fn write_value(value: &JsonValue, out: &mut String) -> HalResult<()> {
+    match value {
+        JsonValue::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(']');
+        }
+        JsonValue::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&to_string(key)?);
+                out.push(':');
+                write_value(item, out)?;
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&to_string(scalar)?),
+    }
+    Ok(())
+}
+
+/// Drop a `"templated": false` property from `link`, the default anyway.
+fn drop_default_templated(link: &mut JsonValue) {
+    if let JsonValue::Object(link) = link {
+        if link.get("templated") == Some(&JsonValue::Bool(false)) {
+            link.remove("templated");
+        }
+    }
+}
+
+/// Normalize the single-vs-array form of links and their `templated`
+/// property, recursively through `_embedded`.
+fn normalize(value: &mut JsonValue) {
+    let map = match value {
+        JsonValue::Object(map) => map,
+        _ => return,
+    };
+    if let Some(JsonValue::Object(links)) = map.get_mut("_links") {
+        for (rel, link) in links.iter_mut() {
+            let single = match link {
+                JsonValue::Array(items) if items.len() == 1 && rel != "curies" => items.pop(),
+                _ => None,
+            };
+            if let Some(single) = single {
+                *link = single;
+            } else if rel == "curies" && link.is_object() {
+                *link = JsonValue::Array(vec![link.take()]);
+            }
+            match link {
+                JsonValue::Array(items) => items.iter_mut().for_each(drop_default_templated),
+                link => drop_default_templated(link),
+            }
+        }
+    }
+    if let Some(JsonValue::Object(curies)) = map.get_mut("_curies") {
+        curies.values_mut().for_each(drop_default_templated);
+    }
+    if let Some(JsonValue::Object(embedded)) = map.get_mut("_embedded") {
+        for resources in embedded.values_mut() {
+            match resources {
+                JsonValue::Array(items) => items.iter_mut().for_each(normalize),
+                resource => normalize(resource),
+            }
+        }
+    }
+}
+
+/// The canonical form of a serialized HAL document.
+pub fn to_canonical_string(document: &JsonValue) -> HalResult<String> {
+    let mut document = document.clone();
+    normalize(&mut document);
+    let mut out = String::new();
+    write_value(&document, &mut out)?;
+    Ok(out)
+}
+
+/// A strong entity tag for the exact bytes of `body`: their quoted, hex
+/// encoded SHA-256 digest.
+pub fn etag(body: &str) -> String {
+    let digest = Sha256::digest(body.as_bytes());
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+/// A weak entity tag for `canonical`, a canonical form.
+pub fn weak_etag(canonical: &str) -> String {
+    format!("W/{}", etag(canonical))
+}
+
+/// Whether an `If-None-Match` header value matches `etag`, using the weak
+/// comparison RFC 9110 requires for that header.
+pub fn if_none_match(header: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    header.trim() == "*" || header.split(',').any(|tag| opaque(tag) == opaque(etag))
+}
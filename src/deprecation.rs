@@ -0,0 +1,205 @@
+//! Notification of deprecated link traversal.
+//!
+//! The [HAL Draft Spec](https://tools.ietf.org/html/draft-kelly-json-hal-08#section-5.4)
+//! says a client SHOULD provide some notification whenever it traverses a link
+//! carrying a `deprecation` property. Every lookup through
+//! [`HalResource::get_link`](../resource/struct.HalResource.html#method.get_link),
+//! `get_links` or `follow` reports deprecated links to a `DeprecationObserver`.
+//!
+//! By default the notification is a warning logged through the `log` facade.
+//! A process-wide observer can be installed with `set_observer`, and a single
+//! lookup can use its own observer with `HalResource::follow_with`.
+//!
+//! A `Deprecation` describes a deprecation in machine-readable form: when it
+//! happened, when the link or route goes away ([RFC 8594](https://tools.ietf.org/html/rfc8594)
+//! `Sunset`) and which relation replaces it. It can be attached to a link with
+//! `HalLink::with_deprecation_notice`, or to a response with
+//! `HalResponse::with_deprecation` to send the `Deprecation` and `Sunset`
+//! headers.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::deprecation::StrictObserver;
+//! use rustic_hal::{HalLink, HalResource};
+//!
+//! let res = HalResource::new(())
+//!     .with_link("old", HalLink::new("/old").with_deprecation("http://docs/old"));
+//!
+//! assert!(res.follow_with("old", &StrictObserver).is_err());
+//! ```
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::link::HalLink;
+use super::{HalError, HalResult};
+
+/// Receives a notification every time a deprecated link is looked up.
+pub trait DeprecationObserver: Send + Sync {
+    /// Called with the relation and the link whose `deprecation` property is set.
+    ///
+    /// Returning an error makes `follow` fail; `get_link` and `get_links`
+    /// cannot report errors and only log them.
+    fn on_deprecated(&self, rel: &str, link: &HalLink) -> HalResult<()>;
+}
+
+/// The default observer: logs a warning through the `log` facade.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogObserver;
+
+impl DeprecationObserver for LogObserver {
+    fn on_deprecated(&self, rel: &str, link: &HalLink) -> HalResult<()> {
+        log::warn!(
+            "traversing deprecated link '{}' ({}), see {}",
+            rel,
+            link.href,
+            link.deprecation.as_deref().unwrap_or("")
+        );
+        Ok(())
+    }
+}
+
+/// An observer that rejects any deprecated link, to fail hard in tests.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StrictObserver;
+
+impl DeprecationObserver for StrictObserver {
+    fn on_deprecated(&self, rel: &str, link: &HalLink) -> HalResult<()> {
+        Err(HalError::DeprecatedLink(
+            rel.to_string(),
+            link.deprecation.clone().unwrap_or_default(),
+        ))
+    }
+}
+
+static OBSERVER: RwLock<Option<Arc<dyn DeprecationObserver>>> = RwLock::new(None);
+
+/// Install the process-wide observer used by `get_link`, `get_links` and `follow`.
+pub fn set_observer<O>(observer: O)
+where
+    O: DeprecationObserver + 'static,
+{
+    *OBSERVER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(observer));
+}
+
+/// Restore the default `LogObserver`.
+pub fn reset_observer() {
+    *OBSERVER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Report `link` to `observer` if it is deprecated.
+pub fn check_with(observer: &dyn DeprecationObserver, rel: &str, link: &HalLink) -> HalResult<()> {
+    if link.deprecation.is_some() {
+        observer.on_deprecated(rel, link)
+    } else {
+        Ok(())
+    }
+}
+
+/// Report `link` to the process-wide observer if it is deprecated.
+pub fn check(rel: &str, link: &HalLink) -> HalResult<()> {
+    if link.deprecation.is_none() {
+        return Ok(());
+    }
+    let observer = OBSERVER.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match observer {
+        Some(observer) => observer.on_deprecated(rel, link),
+        None => LogObserver.on_deprecated(rel, link),
+    }
+}
+
+/// `time` as an HTTP-date, clamped to the dates it can hold: from 1970 to
+/// the end of year 9999.
+pub(crate) fn http_date(time: SystemTime) -> String {
+    let last = UNIX_EPOCH + Duration::from_secs(253_402_300_799);
+    httpdate::fmt_http_date(time.clamp(UNIX_EPOCH, last))
+}
+
+/// A deprecation notice, with an optional sunset date and replacement.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Deprecation {
+    date: Option<SystemTime>,
+    sunset: Option<SystemTime>,
+    info: Option<String>,
+    replacement: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new() -> Deprecation {
+        Deprecation::default()
+    }
+
+    /// When the deprecation took or takes effect.
+    pub fn with_date(mut self, date: SystemTime) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// When the deprecated link or route stops working.
+    pub fn with_sunset(mut self, sunset: SystemTime) -> Self {
+        self.sunset = Some(sunset);
+        self
+    }
+
+    /// A URL documenting the deprecation.
+    pub fn with_info(mut self, url: &str) -> Self {
+        self.info = Some(url.to_string());
+        self
+    }
+
+    /// The relation to use instead.
+    pub fn with_replacement(mut self, rel: &str) -> Self {
+        self.replacement = Some(rel.to_string());
+        self
+    }
+
+    pub fn date(&self) -> Option<SystemTime> {
+        self.date
+    }
+
+    pub fn sunset(&self) -> Option<SystemTime> {
+        self.sunset
+    }
+
+    pub fn info(&self) -> Option<&str> {
+        self.info.as_deref()
+    }
+
+    pub fn replacement(&self) -> Option<&str> {
+        self.replacement.as_deref()
+    }
+
+    /// Whether the sunset date is past at `now`.
+    pub fn is_sunset(&self, now: SystemTime) -> bool {
+        self.sunset.is_some_and(|sunset| now >= sunset)
+    }
+
+    /// The `Deprecation`, `Sunset` and `Link` response headers announcing it.
+    ///
+    /// The `Deprecation` header holds the date as a structured field
+    /// (`@1688169599`), or `true` without one as earlier drafts did. The
+    /// `Sunset` date is clamped to the years 1970 to 9999.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        let date = match self.date {
+            Some(date) => {
+                let secs = match date.duration_since(UNIX_EPOCH) {
+                    Ok(d) => d.as_secs() as i64,
+                    Err(e) => -(e.duration().as_secs() as i64),
+                };
+                format!("@{}", secs)
+            }
+            None => "true".to_string(),
+        };
+        let mut headers = vec![("Deprecation".to_string(), date)];
+        if let Some(sunset) = self.sunset {
+            headers.push(("Sunset".to_string(), http_date(sunset)));
+        }
+        if let Some(ref info) = self.info {
+            headers.push((
+                "Link".to_string(),
+                format!(r#"<{}>; rel="deprecation"; type="text/html""#, info),
+            ));
+        }
+        headers
+    }
+}
@@ -0,0 +1,309 @@
+//! Embedding related resources on demand.
+//!
+//! A client asks for related resources to be embedded with an `embed` (or
+//! `zoom`) query parameter, such as `?embed=customer,items.product`: embed the
+//! customer and the items, and the product of every item. Relations that are
+//! not asked for stay as plain `_links`.
+//!
+//! An `EmbedResolver` declares which relations of a resource can be embedded
+//! and how to load them. Nested paths are resolved by the resolver registered
+//! for the parent relation with `with_nested`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::embed::{EmbedResolver, EmbedSpec};
+//! use rustic_hal::HalResource;
+//!
+//! let resolver = EmbedResolver::new()
+//!     .with_loader("customer", |order: &HalResource| {
+//!         let href = &order.get_link("customer").unwrap().href;
+//!         Ok(HalResource::new(()).with_link("self", href.as_str()))
+//!     })
+//!     .with_loader("items", |_: &HalResource| Ok(Vec::<HalResource>::new()));
+//!
+//! let order = HalResource::new(())
+//!     .with_link("customer", "/customers/3")
+//!     .with_link("items", "/orders/12/items");
+//! let spec = EmbedSpec::from_query("embed=customer");
+//! let order = resolver.resolve(order, &spec).unwrap();
+//!
+//! assert!(order.get_resource("customer").is_some());
+//! assert!(order.get_resources("items").is_none());
+//! ```
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use super::resource::HalResource;
+use super::uri;
+use super::{HalError, HalResult};
+
+/// The default limit on the length of an embed path.
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// The relations a client asked to embed, as a tree of relation names.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmbedSpec {
+    rels: BTreeMap<String, EmbedSpec>,
+}
+
+impl EmbedSpec {
+    pub fn new() -> EmbedSpec {
+        EmbedSpec::default()
+    }
+
+    /// Parse a comma separated list of dotted paths, such as
+    /// `customer,items.product`, ignoring path segments beyond
+    /// `DEFAULT_MAX_DEPTH`.
+    pub fn parse(paths: &str) -> EmbedSpec {
+        EmbedSpec::new().with_paths(paths, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Read the `embed` and `zoom` parameters of a query string (without the `?`).
+    pub fn from_query(query: &str) -> EmbedSpec {
+        EmbedSpec::from_query_with_depth(query, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Same as `from_query`, ignoring path segments beyond `max_depth`.
+    pub fn from_query_with_depth(query: &str, max_depth: usize) -> EmbedSpec {
+        uri::query_pairs(query)
+            .into_iter()
+            .filter(|(name, _)| name == "embed" || name == "zoom")
+            .fold(EmbedSpec::new(), |spec, (_, paths)| {
+                spec.with_paths(&paths, max_depth)
+            })
+    }
+
+    /// Add a comma separated list of dotted paths.
+    pub fn with_paths(mut self, paths: &str, max_depth: usize) -> Self {
+        for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let mut node = &mut self;
+            for rel in path.split('.').filter(|r| !r.is_empty()).take(max_depth) {
+                node = node.rels.entry(rel.to_string()).or_default();
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rels.is_empty()
+    }
+
+    /// Whether `rel` was asked for.
+    pub fn contains(&self, rel: &str) -> bool {
+        self.rels.contains_key(rel)
+    }
+
+    /// The paths asked for below `rel`.
+    pub fn nested(&self, rel: &str) -> Option<&EmbedSpec> {
+        self.rels.get(rel)
+    }
+
+    /// The relations asked for at this level.
+    pub fn rels(&self) -> impl Iterator<Item = &str> {
+        self.rels.keys().map(String::as_str)
+    }
+}
+
+/// What a loader embeds: a single resource, or an array of resources.
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Embedded {
+    One(HalResource),
+    Many(Vec<HalResource>),
+    None,
+}
+
+impl From<HalResource> for Embedded {
+    fn from(resource: HalResource) -> Self {
+        Embedded::One(resource)
+    }
+}
+
+impl From<Option<HalResource>> for Embedded {
+    fn from(resource: Option<HalResource>) -> Self {
+        resource.map_or(Embedded::None, Embedded::One)
+    }
+}
+
+impl From<Vec<HalResource>> for Embedded {
+    fn from(resources: Vec<HalResource>) -> Self {
+        Embedded::Many(resources)
+    }
+}
+
+/// A boxed future, as returned by asynchronous loaders.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+type SyncLoader = Box<dyn Fn(&HalResource) -> HalResult<Embedded> + Send + Sync>;
+type AsyncLoader =
+    Box<dyn Fn(&HalResource) -> BoxFuture<'static, HalResult<Embedded>> + Send + Sync>;
+
+enum Loader {
+    Sync(SyncLoader),
+    Async(AsyncLoader),
+}
+
+/// The relations of a resource that can be embedded, with their loaders.
+#[derive(Default)]
+pub struct EmbedResolver {
+    loaders: BTreeMap<String, Loader>,
+    nested: BTreeMap<String, EmbedResolver>,
+}
+
+impl EmbedResolver {
+    pub fn new() -> EmbedResolver {
+        EmbedResolver::default()
+    }
+
+    /// Declare `rel` as embeddable, loaded from the parent resource by `loader`.
+    pub fn with_loader<F, E>(mut self, rel: &str, loader: F) -> Self
+    where
+        F: Fn(&HalResource) -> HalResult<E> + Send + Sync + 'static,
+        E: Into<Embedded>,
+    {
+        let loader: SyncLoader = Box::new(move |res| loader(res).map(Into::into));
+        self.loaders.insert(rel.to_string(), Loader::Sync(loader));
+        self
+    }
+
+    /// Declare `rel` as embeddable, loaded asynchronously by `loader`.
+    ///
+    /// Asynchronous loaders are only run by `resolve_async`.
+    pub fn with_async_loader<F, Fut, E>(mut self, rel: &str, loader: F) -> Self
+    where
+        F: Fn(&HalResource) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HalResult<E>> + Send + 'static,
+        E: Into<Embedded>,
+    {
+        let loader: AsyncLoader = Box::new(move |res| {
+            let fut = loader(res);
+            Box::pin(async move { fut.await.map(Into::into) })
+        });
+        self.loaders.insert(rel.to_string(), Loader::Async(loader));
+        self
+    }
+
+    /// Use `resolver` for the resources embedded under `rel`, to resolve
+    /// nested paths such as `items.product`.
+    pub fn with_nested(mut self, rel: &str, resolver: EmbedResolver) -> Self {
+        self.nested.insert(rel.to_string(), resolver);
+        self
+    }
+
+    /// Embed the relations asked for by `spec`, running synchronous loaders only.
+    ///
+    /// Fails if a relation asked for only has an asynchronous loader.
+    /// Relations without a loader are ignored, but nested paths still apply
+    /// to resources already embedded under them.
+    pub fn resolve(&self, mut resource: HalResource, spec: &EmbedSpec) -> HalResult<HalResource> {
+        for rel in spec.rels() {
+            let embedded = match self.loaders.get(rel) {
+                Some(Loader::Sync(loader)) => loader(&resource)?,
+                Some(Loader::Async(_)) => {
+                    return Err(HalError::Custom(format!(
+                        "Relation {} can only be embedded asynchronously",
+                        rel
+                    )))
+                }
+                None => Embedded::None,
+            };
+            resource = embed(resource, rel, embedded);
+            if let (Some(nested), Some(resolver)) = (spec.nested(rel), self.nested.get(rel)) {
+                if let Some(children) = resource.get_resources_mut(rel) {
+                    for child in children.iter_mut() {
+                        *child = resolver.resolve(child.clone(), nested)?;
+                    }
+                }
+            }
+        }
+        Ok(resource)
+    }
+
+    /// Embed the relations asked for by `spec`, running all loaders.
+    pub fn resolve_async<'a>(
+        &'a self,
+        mut resource: HalResource,
+        spec: &'a EmbedSpec,
+    ) -> BoxFuture<'a, HalResult<HalResource>> {
+        Box::pin(async move {
+            for rel in spec.rels() {
+                let embedded = match self.loaders.get(rel) {
+                    Some(Loader::Sync(loader)) => loader(&resource)?,
+                    Some(Loader::Async(loader)) => loader(&resource).await?,
+                    None => Embedded::None,
+                };
+                resource = embed(resource, rel, embedded);
+                if let (Some(nested), Some(resolver)) = (spec.nested(rel), self.nested.get(rel)) {
+                    if let Some(children) = resource.get_resources_mut(rel) {
+                        for child in children.iter_mut() {
+                            *child = resolver.resolve_async(child.clone(), nested).await?;
+                        }
+                    }
+                }
+            }
+            Ok(resource)
+        })
+    }
+}
+
+pub(crate) fn embed(resource: HalResource, rel: &str, embedded: Embedded) -> HalResource {
+    match embedded {
+        Embedded::One(one) => resource.with_resource(rel, one),
+        Embedded::Many(many) => resource.with_resources(rel, many),
+        Embedded::None => resource,
+    }
+}
+
+#[cfg(feature = "axumweb")]
+mod axum {
+    use super::EmbedSpec;
+    use axum::extract::FromRequestParts;
+    use axum::http::request::Parts;
+    use std::convert::Infallible;
+
+    impl<S: Send + Sync> FromRequestParts<S> for EmbedSpec {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+            Ok(EmbedSpec::from_query(parts.uri.query().unwrap_or("")))
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+    use super::EmbedSpec;
+    use actix_web::dev::Payload;
+    use actix_web::{FromRequest, HttpRequest};
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    impl FromRequest for EmbedSpec {
+        type Error = Infallible;
+        type Future = Ready<Result<Self, Self::Error>>;
+
+        fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+            ready(Ok(EmbedSpec::from_query(req.query_string())))
+        }
+    }
+}
+
+#[cfg(feature = "warp-reply")]
+mod warp {
+    use super::EmbedSpec;
+    use std::convert::Infallible;
+    use warp::Filter;
+
+    /// A filter extracting the `embed` query parameter.
+    pub fn embed_spec() -> impl Filter<Extract = (EmbedSpec,), Error = Infallible> + Clone {
+        warp::query::raw()
+            .or(warp::any().map(String::new))
+            .unify()
+            .map(|query: String| EmbedSpec::from_query(&query))
+    }
+}
+
+#[cfg(feature = "warp-reply")]
+pub use self::warp::embed_spec;
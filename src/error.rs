@@ -6,6 +6,16 @@ use std::fmt;
 pub enum HalError {
     Json(JsonError),
     Custom(String),
+    /// A deprecated link was rejected: relation and deprecation URL.
+    DeprecatedLink(String, String),
+    /// A relation looks like a typo: relation and the registered one it resembles.
+    SuspiciousRel(String, String),
+    /// A payload key collides with the HAL structure.
+    ReservedKey(String),
+    /// A patch is malformed or cannot be applied.
+    InvalidPatch(String),
+    /// A patch `test` operation failed.
+    PatchConflict(String),
 }
 
 pub type HalResult<T> = Result<T, HalError>;
@@ -15,19 +25,52 @@ impl fmt::Display for HalError {
         match *self {
             HalError::Json(ref e) => write!(f, "JSON Error: {}", e),
             HalError::Custom(ref s) => write!(f, "Notify error: {}", s),
+            HalError::DeprecatedLink(ref rel, ref url) => {
+                write!(f, "Deprecated link '{}', see {}", rel, url)
+            }
+            HalError::SuspiciousRel(ref rel, ref reg) => {
+                write!(f, "Unknown relation '{}', did you mean '{}'?", rel, reg)
+            }
+            HalError::ReservedKey(ref key) => write!(f, "Reserved key {} in payload", key),
+            HalError::InvalidPatch(ref s) => write!(f, "Invalid patch: {}", s),
+            HalError::PatchConflict(ref s) => write!(f, "Patch conflict: {}", s),
         }
     }
 }
 
-impl Error for HalError {
-    fn description(&self) -> &str {
+impl HalError {
+    /// A short, human-readable summary of the kind of error.
+    pub fn title(&self) -> &'static str {
         match *self {
             HalError::Json(_) => "Error in json processing",
             HalError::Custom(_) => "Internal Hal Error",
+            HalError::DeprecatedLink(_, _) => "Deprecated link traversed",
+            HalError::SuspiciousRel(_, _) => "Suspicious link relation",
+            HalError::ReservedKey(_) => "Reserved payload key",
+            HalError::InvalidPatch(_) => "Invalid patch",
+            HalError::PatchConflict(_) => "Patch conflict",
+        }
+    }
+
+    /// The HTTP status code to answer with when the error reaches a handler.
+    pub fn status(&self) -> u16 {
+        match *self {
+            HalError::Json(_)
+            | HalError::Custom(_)
+            | HalError::DeprecatedLink(_, _)
+            | HalError::SuspiciousRel(_, _) => 500,
+            HalError::ReservedKey(_) | HalError::InvalidPatch(_) => 422,
+            HalError::PatchConflict(_) => 409,
         }
     }
 }
 
+impl Error for HalError {
+    fn description(&self) -> &str {
+        self.title()
+    }
+}
+
 impl From<JsonError> for HalError {
     fn from(error: JsonError) -> Self {
         HalError::Json(error)
@@ -0,0 +1,156 @@
+//! Sparse fieldsets: keeping only the payload fields a client asked for.
+//!
+//! The `fields` query parameter lists the payload fields to keep on the
+//! resource itself, and `fields[rel]` the ones to keep on the resources
+//! embedded under `rel` (`fields[items.product]` for deeper ones). Resources
+//! without a fieldset keep their whole payload, and links are always kept
+//! unless asked otherwise.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::fields::Fieldset;
+//! use rustic_hal::HalResource;
+//! use serde_json::{json, to_value};
+//!
+//! let order = HalResource::new(json!({"id": 12, "total": 30, "notes": "..."}))
+//!     .with_link("self", "/orders/12")
+//!     .with_resource("items", HalResource::new(json!({"sku": "a", "qty": 1, "price": 30})));
+//!
+//! let fields = Fieldset::from_query("fields=id,total&fields[items]=sku,qty");
+//! assert_eq!(
+//!     to_value(order.project(&fields)).unwrap(),
+//!     json!({
+//!         "_links": {"self": {"href": "/orders/12"}},
+//!         "_embedded": {"items": {"sku": "a", "qty": 1}},
+//!         "id": 12,
+//!         "total": 30
+//!     })
+//! );
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::uri;
+
+/// Relations kept when stripping non-essential links.
+pub const ESSENTIAL_RELS: &[&str] = &["self", "curies"];
+
+/// The payload fields to keep, per embedded relation path.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fieldset {
+    fields: BTreeMap<String, BTreeSet<String>>,
+    strip_links: bool,
+}
+
+impl Fieldset {
+    pub fn new() -> Fieldset {
+        Fieldset::default()
+    }
+
+    /// Read the `fields` and `fields[rel]` parameters of a query string
+    /// (without the `?`).
+    pub fn from_query(query: &str) -> Fieldset {
+        uri::query_pairs(query)
+            .into_iter()
+            .fold(Fieldset::new(), |fieldset, (name, value)| {
+                let path = if name == "fields" {
+                    Some("")
+                } else {
+                    name.strip_prefix("fields[").and_then(|n| n.strip_suffix(']'))
+                };
+                match path {
+                    Some(path) => fieldset.with_fields(path, value.split(',')),
+                    None => fieldset,
+                }
+            })
+    }
+
+    /// Keep `fields` on the resources at `path`: `""` for the resource
+    /// itself, a relation for the resources embedded under it, or a dotted
+    /// path such as `items.product`.
+    pub fn with_fields<I, S>(mut self, path: &str, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let set = self.fields.entry(path.to_string()).or_default();
+        set.extend(
+            fields
+                .into_iter()
+                .map(|f| f.as_ref().trim().to_string())
+                .filter(|f| !f.is_empty()),
+        );
+        self
+    }
+
+    /// Also drop every link but the `self` link and curies.
+    pub fn strip_links(mut self, strip: bool) -> Self {
+        self.strip_links = strip;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && !self.strip_links
+    }
+
+    /// The fields to keep at `path`, or `None` to keep them all.
+    pub fn fields(&self, path: &str) -> Option<&BTreeSet<String>> {
+        self.fields.get(path)
+    }
+
+    pub fn strips_links(&self) -> bool {
+        self.strip_links
+    }
+}
+
+#[cfg(feature = "axumweb")]
+mod axum {
+    use super::Fieldset;
+    use axum::extract::FromRequestParts;
+    use axum::http::request::Parts;
+    use std::convert::Infallible;
+
+    impl<S: Send + Sync> FromRequestParts<S> for Fieldset {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+            Ok(Fieldset::from_query(parts.uri.query().unwrap_or("")))
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+    use super::Fieldset;
+    use actix_web::dev::Payload;
+    use actix_web::{FromRequest, HttpRequest};
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    impl FromRequest for Fieldset {
+        type Error = Infallible;
+        type Future = Ready<Result<Self, Self::Error>>;
+
+        fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+            ready(Ok(Fieldset::from_query(req.query_string())))
+        }
+    }
+}
+
+#[cfg(feature = "warp-reply")]
+mod warp {
+    use super::Fieldset;
+    use std::convert::Infallible;
+    use warp::Filter;
+
+    /// A filter extracting the `fields` query parameters.
+    pub fn fieldset() -> impl Filter<Extract = (Fieldset,), Error = Infallible> + Clone {
+        warp::query::raw()
+            .or(warp::any().map(String::new))
+            .unify()
+            .map(|query: String| Fieldset::from_query(&query))
+    }
+}
+
+#[cfg(feature = "warp-reply")]
+pub use self::warp::fieldset;
@@ -0,0 +1,393 @@
+//! Absolute links from the request's origin, behind proxies.
+//!
+//! A `RequestOrigin` is the scheme, host and path prefix a client used to
+//! reach the service. It is read from the `Host` header, and from the
+//! `Forwarded` ([RFC 7239](https://tools.ietf.org/html/rfc7239)),
+//! `X-Forwarded-Proto`, `X-Forwarded-Host` and `X-Forwarded-Prefix` headers
+//! when the request comes from a proxy listed in `TrustedProxies`: anyone
+//! else could spoof them. Proxies append to these headers, or add lines of
+//! their own, so the lines of each are joined and read from right to left:
+//! the entry used is the one written by the outermost trusted proxy,
+//! whatever the client put in front of it.
+//! `HalResponse::with_origin` then makes the absolute-path hrefs (`/…`) of
+//! the response absolute. Relative-path hrefs (`items`, `../up`) depend on
+//! the full request URI, not only its origin, and are left as they are.
+//!
+//! With the `axumweb` and `actix` features, `RequestOrigin` is an extractor
+//! reading the `TrustedProxies` from the request extensions (axum) or the
+//! app data (actix), and the peer address from `ConnectInfo<SocketAddr>`
+//! (axum). With `warp-reply`, the `request_origin` filter does the same.
+//! Requests that do not tell their scheme get the one of
+//! `TrustedProxies::with_scheme`, or `https` for actix servers bound with
+//! TLS.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::forwarded::{RequestOrigin, TrustedProxies};
+//! use rustic_hal::HalResource;
+//!
+//! let proxies = TrustedProxies::new().with_proxy("10.0.0.0/8").unwrap();
+//! let headers = vec![
+//!     ("Host", "orders-svc:8080"),
+//!     ("X-Forwarded-Proto", "https"),
+//!     ("X-Forwarded-Host", "api.acme.com"),
+//!     ("X-Forwarded-Prefix", "/shop"),
+//! ];
+//! let origin = RequestOrigin::from_headers(headers, "http", "10.1.2.3".parse().ok(), &proxies);
+//!
+//! let res = origin.absolutize(HalResource::new(()).with_link("self", "/orders/12"));
+//! assert_eq!(res.get_self().unwrap().href, "https://api.acme.com/shop/orders/12");
+//! ```
+use std::net::IpAddr;
+
+use super::resource::HalResource;
+use super::{HalError, HalResult};
+
+/// The proxies whose forwarding headers are trusted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrustedProxies {
+    ranges: Vec<(IpAddr, u8)>,
+    any: bool,
+    scheme: Option<String>,
+}
+
+fn bits(ip: &IpAddr) -> (u128, u8) {
+    match ip {
+        IpAddr::V4(ip) => (u32::from(*ip) as u128, 32),
+        IpAddr::V6(ip) => (u128::from(*ip), 128),
+    }
+}
+
+impl TrustedProxies {
+    /// Trust no proxy.
+    pub fn new() -> TrustedProxies {
+        TrustedProxies::default()
+    }
+
+    /// Trust every peer, when the service cannot be reached but through
+    /// proxies.
+    pub fn any() -> TrustedProxies {
+        TrustedProxies {
+            any: true,
+            ..TrustedProxies::default()
+        }
+    }
+
+    /// The scheme of requests that do not tell theirs, `http` by default.
+    /// Services only reachable over TLS, or behind a proxy terminating it
+    /// without forwarding headers, should set `https`.
+    pub fn with_scheme(mut self, scheme: &str) -> Self {
+        self.scheme = Some(scheme.to_ascii_lowercase());
+        self
+    }
+
+    /// The scheme of requests that do not tell theirs.
+    pub fn scheme(&self) -> &str {
+        self.scheme.as_deref().unwrap_or("http")
+    }
+
+    /// Trust an address (`10.0.0.1`) or a CIDR range (`10.0.0.0/8`).
+    pub fn with_proxy(mut self, proxy: &str) -> HalResult<Self> {
+        let invalid = || HalError::Custom(format!("Invalid proxy address {}", proxy));
+        let (ip, len) = match proxy.find('/') {
+            Some(i) => (&proxy[..i], Some(&proxy[i + 1..])),
+            None => (proxy, None),
+        };
+        let ip: IpAddr = ip.trim().parse().map_err(|_| invalid())?;
+        let max = bits(&ip).1;
+        let len = match len {
+            Some(len) => len.trim().parse::<u8>().map_err(|_| invalid())?,
+            None => max,
+        };
+        if len > max {
+            return Err(invalid());
+        }
+        self.ranges.push((ip, len));
+        Ok(self)
+    }
+
+    /// Whether `peer` is a trusted proxy.
+    pub fn trusts(&self, peer: Option<IpAddr>) -> bool {
+        if self.any {
+            return true;
+        }
+        let peer = match peer {
+            Some(IpAddr::V6(v6)) => match v6.to_ipv4_mapped() {
+                Some(v4) => IpAddr::V4(v4),
+                None => IpAddr::V6(v6),
+            },
+            Some(peer) => peer,
+            None => return false,
+        };
+        let (peer, width) = bits(&peer);
+        self.ranges.iter().any(|(ip, len)| {
+            let (ip, ip_width) = bits(ip);
+            if ip_width != width {
+                return false;
+            }
+            let mask = match *len {
+                0 => 0,
+                len => u128::MAX << (width - len),
+            };
+            peer & mask == ip & mask
+        })
+    }
+}
+
+/// The scheme, host and path prefix the client used.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequestOrigin {
+    pub scheme: String,
+    /// The host, with its port if any; empty when unknown.
+    pub host: String,
+    /// A path prefix added by a proxy, without trailing `/`.
+    pub prefix: String,
+}
+
+/// The value of `name` in an element of a `Forwarded` header.
+fn forwarded_param(element: &str, name: &str) -> Option<String> {
+    element.split(';').find_map(|pair| {
+        let i = pair.find('=')?;
+        if pair[..i].trim().eq_ignore_ascii_case(name) {
+            Some(pair[i + 1..].trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// The address of a node, such as `192.0.2.60`, `"[2001:db8::1]:4711"` or
+/// `10.0.0.1:80`; `None` for obfuscated or unknown nodes.
+fn node_ip(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    node.parse()
+        .ok()
+        .or_else(|| node.rsplit_once(':')?.0.parse().ok())
+}
+
+/// The index of the entry written by the outermost trusted proxy, given the
+/// nodes each entry was received from. The last entry comes from the peer,
+/// trusted; walking left, the first entry received from a node that is not
+/// a trusted proxy is the one, or the leftmost if all are.
+fn trusted_index(nodes: &[Option<IpAddr>], trusted: &TrustedProxies) -> usize {
+    nodes.iter().rposition(|ip| !trusted.trusts(*ip)).unwrap_or(0)
+}
+
+/// The entries of a comma-separated list.
+fn entries(value: &str) -> Vec<&str> {
+    value.split(',').map(str::trim).collect()
+}
+
+impl RequestOrigin {
+    /// Read the origin from request headers. The forwarding headers are only
+    /// used if `peer`, the address the request came from, is trusted, and
+    /// only up to the first hop that is not; `scheme` is the one the request
+    /// was received with.
+    pub fn from_headers<'a, I>(
+        headers: I,
+        scheme: &str,
+        peer: Option<IpAddr>,
+        trusted: &TrustedProxies,
+    ) -> RequestOrigin
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut origin = RequestOrigin {
+            scheme: scheme.to_string(),
+            ..RequestOrigin::default()
+        };
+        let headers: Vec<_> = headers.into_iter().collect();
+        // All the lines of a header, as one list: a proxy may add its own line
+        // instead of appending to the client's.
+        let get = |name: &str| {
+            let lines: Vec<&str> = headers
+                .iter()
+                .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| *v)
+                .collect();
+            if lines.is_empty() {
+                None
+            } else {
+                Some(lines.join(","))
+            }
+        };
+        if let Some((_, host)) = headers.iter().find(|(n, _)| n.eq_ignore_ascii_case("host")) {
+            origin.host = host.trim().to_string();
+        }
+        if !trusted.trusts(peer) {
+            return origin;
+        }
+        let forwarded_for = get("x-forwarded-for").unwrap_or_default();
+        let hops: Vec<&str> = if forwarded_for.is_empty() {
+            Vec::new()
+        } else {
+            entries(&forwarded_for)
+        };
+        let nodes: Vec<_> = hops.iter().map(|hop| node_ip(hop)).collect();
+        let index = trusted_index(&nodes, trusted);
+        // The entry of a X-Forwarded-* list matching the X-Forwarded-For one,
+        // or the last one when the proxies did not fill every list.
+        let pick = |value: &str| {
+            let values = entries(value);
+            if values.len() == hops.len() {
+                values[index].to_string()
+            } else {
+                values[values.len() - 1].to_string()
+            }
+        };
+        if let Some(forwarded) = get("forwarded") {
+            let elements = entries(&forwarded);
+            let nodes: Vec<_> = elements
+                .iter()
+                .map(|element| forwarded_param(element, "for").as_deref().and_then(node_ip))
+                .collect();
+            let element = elements[trusted_index(&nodes, trusted)];
+            if let Some(proto) = forwarded_param(element, "proto") {
+                origin.scheme = proto;
+            }
+            if let Some(host) = forwarded_param(element, "host") {
+                origin.host = host;
+            }
+        } else {
+            if let Some(proto) = get("x-forwarded-proto") {
+                origin.scheme = pick(&proto);
+            }
+            if let Some(host) = get("x-forwarded-host") {
+                origin.host = pick(&host);
+            }
+        }
+        if let Some(prefix) = get("x-forwarded-prefix") {
+            let prefix = pick(&prefix);
+            let prefix = prefix.trim_end_matches('/');
+            if !prefix.is_empty() {
+                origin.prefix = format!("/{}", prefix.trim_start_matches('/'));
+            }
+        }
+        origin.scheme = origin.scheme.to_ascii_lowercase();
+        origin
+    }
+
+    /// The base URL, `scheme://host/prefix/`, or `None` if the host is unknown.
+    pub fn base(&self) -> Option<String> {
+        if self.host.is_empty() {
+            None
+        } else {
+            Some(format!("{}://{}{}/", self.scheme, self.host, self.prefix))
+        }
+    }
+
+    /// Make `href` absolute, with the prefix, if it is an absolute path;
+    /// other hrefs are returned as they are.
+    pub fn absolutize_href(&self, href: &str) -> String {
+        if self.host.is_empty() || !href.starts_with('/') || href.starts_with("//") {
+            href.to_string()
+        } else {
+            format!("{}://{}{}{}", self.scheme, self.host, self.prefix, href)
+        }
+    }
+
+    /// Make every absolute-path href of `resource` absolute, recursively.
+    pub fn absolutize(&self, resource: HalResource) -> HalResource {
+        if self.host.is_empty() {
+            return resource;
+        }
+        resource.rewrite_hrefs(|href| self.absolutize_href(href))
+    }
+}
+
+#[cfg(feature = "axumweb")]
+mod axum {
+    use super::{RequestOrigin, TrustedProxies};
+    use axum::extract::{ConnectInfo, FromRequestParts};
+    use axum::http::request::Parts;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+
+    impl<S: Send + Sync> FromRequestParts<S> for RequestOrigin {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+            let trusted = parts.extensions.get::<TrustedProxies>().cloned().unwrap_or_default();
+            let peer = parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip());
+            let headers = parts
+                .headers
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+            let scheme = parts.uri.scheme_str().unwrap_or(trusted.scheme());
+            let mut origin = RequestOrigin::from_headers(headers, scheme, peer, &trusted);
+            if origin.host.is_empty() {
+                origin.host = parts.uri.authority().map(|a| a.to_string()).unwrap_or_default();
+            }
+            Ok(origin)
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+    use super::{RequestOrigin, TrustedProxies};
+    use actix_web::dev::Payload;
+    use actix_web::{web, FromRequest, HttpRequest};
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    impl FromRequest for RequestOrigin {
+        type Error = Infallible;
+        type Future = Ready<Result<Self, Self::Error>>;
+
+        fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+            let trusted = req
+                .app_data::<web::Data<TrustedProxies>>()
+                .map(|data| data.get_ref().clone())
+                .or_else(|| req.app_data::<TrustedProxies>().cloned())
+                .unwrap_or_default();
+            let headers = req
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+            let peer = req.peer_addr().map(|addr| addr.ip());
+            let scheme = match req.uri().scheme_str() {
+                Some(scheme) => scheme,
+                None if req.app_config().secure() => "https",
+                None => trusted.scheme(),
+            };
+            ready(Ok(RequestOrigin::from_headers(headers, scheme, peer, &trusted)))
+        }
+    }
+}
+
+#[cfg(feature = "warp-reply")]
+mod warp {
+    use super::{RequestOrigin, TrustedProxies};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use warp::http::HeaderMap;
+    use warp::Filter;
+
+    /// A filter extracting the `RequestOrigin`, trusting the forwarding
+    /// headers of `trusted` proxies only.
+    pub fn request_origin(
+        trusted: TrustedProxies,
+    ) -> impl Filter<Extract = (RequestOrigin,), Error = Infallible> + Clone {
+        warp::header::headers_cloned()
+            .and(warp::addr::remote())
+            .map(move |headers: HeaderMap, peer: Option<SocketAddr>| {
+                let headers = headers
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+                    .collect::<Vec<_>>();
+                let peer = peer.map(|p| p.ip());
+                RequestOrigin::from_headers(headers, trusted.scheme(), peer, &trusted)
+            })
+    }
+}
+
+#[cfg(feature = "warp-reply")]
+pub use self::warp::request_origin;
@@ -0,0 +1,107 @@
+//! Identity of resources, for de-duplication and caching.
+//!
+//! By default two resources are equal when their `self` links have the same
+//! href; resources without a `self` link are compared structurally instead.
+//! An `Identity` picks another strategy, and `HalResource::identified` wraps
+//! a resource so that it can be used as a `HashSet` or `HashMap` key under
+//! that strategy.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::collections::HashSet;
+//! use rustic_hal::identity::Identity;
+//! use rustic_hal::HalResource;
+//! use serde_json::json;
+//!
+//! let a = HalResource::new(json!({"id": 1, "name": "a"}));
+//! let b = HalResource::new(json!({"id": 1, "name": "b"}));
+//! assert!(a != b);
+//!
+//! let by_id = Identity::PayloadKey("id".to_string());
+//! let unique: HashSet<_> = vec![a.identified(&by_id), b.identified(&by_id)].into_iter().collect();
+//! assert_eq!(unique.len(), 1);
+//! ```
+use std::hash::{Hash, Hasher};
+
+use serde_json::Value as JsonValue;
+
+use super::resource::HalResource;
+
+/// How to decide whether two resources are the same.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Identity {
+    /// The href of the `self` link; resources without one are compared
+    /// structurally. This is what `PartialEq` and `Hash` use.
+    SelfLink,
+    /// The value of a payload key, such as `id`; resources without it are
+    /// compared structurally.
+    PayloadKey(String),
+    /// The payload, links and embedded resources.
+    Full,
+}
+
+/// A resource borrowed together with the `Identity` to compare it by.
+#[derive(Clone, Copy)]
+pub struct Identified<'a> {
+    resource: &'a HalResource,
+    identity: &'a Identity,
+}
+
+impl<'a> Identified<'a> {
+    pub fn new(resource: &'a HalResource, identity: &'a Identity) -> Identified<'a> {
+        Identified { resource, identity }
+    }
+
+    pub fn resource(&self) -> &'a HalResource {
+        self.resource
+    }
+}
+
+impl<'a> PartialEq for Identified<'a> {
+    fn eq(&self, other: &Identified<'a>) -> bool {
+        self.resource.identity_eq(other.resource, self.identity)
+    }
+}
+
+impl<'a> Eq for Identified<'a> {}
+
+impl<'a> Hash for Identified<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.resource.identity_hash(self.identity, state)
+    }
+}
+
+/// Hash a JSON value, independently of the order of object keys.
+pub fn hash_json<H: Hasher>(value: &JsonValue, state: &mut H) {
+    match value {
+        JsonValue::Null => 0u8.hash(state),
+        JsonValue::Bool(b) => {
+            1u8.hash(state);
+            b.hash(state);
+        }
+        JsonValue::Number(n) => {
+            2u8.hash(state);
+            n.to_string().hash(state);
+        }
+        JsonValue::String(s) => {
+            3u8.hash(state);
+            s.hash(state);
+        }
+        JsonValue::Array(a) => {
+            4u8.hash(state);
+            a.len().hash(state);
+            a.iter().for_each(|v| hash_json(v, state));
+        }
+        JsonValue::Object(m) => {
+            5u8.hash(state);
+            m.len().hash(state);
+            let mut entries: Vec<_> = m.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (k, v) in entries {
+                k.hash(state);
+                hash_json(v, state);
+            }
+        }
+    }
+}
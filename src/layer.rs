@@ -0,0 +1,368 @@
+//! A tower middleware post-processing HAL responses.
+//!
+//! `HalLayer` wraps any tower service answering `http` requests, such as an
+//! axum router or a hyper service. It intercepts the responses with an
+//! `application/hal+json` body and runs them through one pipeline, in this
+//! order:
+//!
+//! 1. the custom steps and link policies, in the order they were added;
+//! 2. sparse fieldsets read from the `fields` query parameters;
+//! 3. href absolutization against the request's origin;
+//! 4. content negotiation to the formats added with `with_format`;
+//! 5. an `ETag` on `200 OK` responses, turned into `304 Not Modified` when
+//!    it matches `If-None-Match`: the weak canonical ETag of the resource
+//!    for HAL, a strong one computed from the body for other formats.
+//!
+//! Other responses go through untouched. Needs the `tower` feature.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use rustic_hal::forwarded::TrustedProxies;
+//! use rustic_hal::layer::HalLayer;
+//!
+//! let app = Router::new()
+//!     .route("/orders/{id}", get(order))
+//!     .layer(
+//!         HalLayer::new()
+//!             .with_origin(TrustedProxies::new().with_proxy("10.0.0.0/8")?)
+//!             .with_fieldsets(true),
+//!     );
+//! ```
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http1::header::{self, HeaderValue};
+use http1::{Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Either, Full};
+use serde_json::{from_slice, to_string};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::canonical;
+use super::fields::Fieldset;
+use super::forwarded::{RequestOrigin, TrustedProxies};
+use super::link_policy::LinkPolicy;
+use super::problem::{Problem, PROBLEM_CONTENT_TYPE};
+use super::resource::HalResource;
+use super::response::HAL_CONTENT_TYPE;
+use super::{HalError, HalResult};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Step = Arc<dyn Fn(&Request<()>, HalResource) -> HalResource + Send + Sync>;
+type Converter = Arc<dyn Fn(&HalResource) -> HalResult<String> + Send + Sync>;
+
+/// The body of the responses of a `HalService`: the inner body when the
+/// response is left alone, the rewritten one otherwise.
+pub type HalBody<B> = Either<B, Full<Bytes>>;
+
+/// A tower layer post-processing HAL responses.
+#[derive(Clone)]
+pub struct HalLayer {
+    steps: Vec<Step>,
+    fieldsets: bool,
+    origin: Option<TrustedProxies>,
+    formats: Vec<(&'static str, Converter)>,
+    etag: bool,
+}
+
+impl Default for HalLayer {
+    fn default() -> Self {
+        HalLayer::new()
+    }
+}
+
+impl HalLayer {
+    /// A layer only adding ETags.
+    pub fn new() -> HalLayer {
+        HalLayer {
+            steps: Vec::new(),
+            fieldsets: false,
+            origin: None,
+            formats: Vec::new(),
+            etag: true,
+        }
+    }
+
+    /// Rewrite every resource with `step`.
+    pub fn with_step<F>(mut self, step: F) -> Self
+    where
+        F: Fn(&Request<()>, HalResource) -> HalResource + Send + Sync + 'static,
+    {
+        self.steps.push(Arc::new(step));
+        self
+    }
+
+    /// Filter links with `policy`, for the context `context` builds from
+    /// the request.
+    pub fn with_link_policy<C, P, F>(self, policy: P, context: F) -> Self
+    where
+        P: LinkPolicy<C> + Send + Sync + 'static,
+        F: Fn(&Request<()>) -> C + Send + Sync + 'static,
+    {
+        self.with_step(move |req, resource| resource.apply_link_policy(&policy, &context(req)))
+    }
+
+    /// Apply the sparse fieldsets of the `fields` query parameters.
+    pub fn with_fieldsets(mut self, enabled: bool) -> Self {
+        self.fieldsets = enabled;
+        self
+    }
+
+    /// Make absolute-path hrefs absolute against the request's origin,
+    /// trusting the forwarding headers of `trusted` proxies. The peer address
+    /// is read from a `SocketAddr` request extension, or axum's `ConnectInfo`.
+    pub fn with_origin(mut self, trusted: TrustedProxies) -> Self {
+        self.origin = Some(trusted);
+        self
+    }
+
+    /// Serve `media_type` to clients preferring it, converting resources
+    /// with `convert`.
+    pub fn with_format<F>(mut self, media_type: &'static str, convert: F) -> Self
+    where
+        F: Fn(&HalResource) -> HalResult<String> + Send + Sync + 'static,
+    {
+        self.formats.push((media_type, Arc::new(convert)));
+        self
+    }
+
+    /// Send `ETag`s and answer `If-None-Match`; on by default.
+    pub fn with_etag(mut self, enabled: bool) -> Self {
+        self.etag = enabled;
+        self
+    }
+
+    fn peer(req: &Request<()>) -> Option<std::net::IpAddr> {
+        if let Some(addr) = req.extensions().get::<SocketAddr>() {
+            return Some(addr.ip());
+        }
+        #[cfg(feature = "axumweb")]
+        {
+            use axum::extract::ConnectInfo;
+            if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
+                return Some(addr.ip());
+            }
+        }
+        None
+    }
+
+    /// Run the pipeline, up to the negotiation.
+    fn process(&self, req: &Request<()>, mut resource: HalResource) -> HalResource {
+        for step in self.steps.iter() {
+            resource = step(req, resource);
+        }
+        if self.fieldsets {
+            resource = resource.project(&Fieldset::from_query(req.uri().query().unwrap_or("")));
+        }
+        if let Some(ref trusted) = self.origin {
+            let headers = req
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+            let scheme = req.uri().scheme_str().unwrap_or(trusted.scheme());
+            let peer = Self::peer(req);
+            let mut origin = RequestOrigin::from_headers(headers, scheme, peer, trusted);
+            if origin.host.is_empty() {
+                origin.host = req.uri().authority().map(|a| a.to_string()).unwrap_or_default();
+            }
+            resource = origin.absolutize(resource);
+        }
+        resource
+    }
+
+    /// The format the client prefers, or `None` for HAL. Each media type
+    /// gets the q-value of the most specific range matching it, and HAL wins
+    /// the ties.
+    fn negotiate(&self, accept: Option<&str>) -> Option<&(&'static str, Converter)> {
+        let accept = accept?;
+        let ranges: Vec<(&str, f32)> = accept
+            .split(',')
+            .map(|range| {
+                let mut params = range.split(';');
+                let media = params.next().unwrap_or("").trim();
+                let q = params
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (media, q)
+            })
+            .collect();
+        let quality = |media: &str| {
+            let media = media.split(';').next().unwrap_or("").trim();
+            ranges
+                .iter()
+                .filter_map(|(range, q)| {
+                    let specificity = if range.eq_ignore_ascii_case(media) {
+                        2
+                    } else if range
+                        .strip_suffix("/*")
+                        .is_some_and(|major| media.split('/').next() == Some(major))
+                    {
+                        1
+                    } else if *range == "*/*" {
+                        0
+                    } else {
+                        return None;
+                    };
+                    Some((specificity, *q))
+                })
+                .max_by_key(|(specificity, _)| *specificity)
+                .map_or(0.0, |(_, q)| q)
+        };
+        let mut best = quality(HAL_CONTENT_TYPE);
+        let mut found = None;
+        for format in self.formats.iter() {
+            let q = quality(format.0);
+            if q > best {
+                best = q;
+                found = Some(format);
+            }
+        }
+        found
+    }
+
+    /// Render `resource` for `req`, with the status and headers of `parts`.
+    fn render(
+        &self,
+        req: &Request<()>,
+        mut parts: http1::response::Parts,
+        resource: HalResource,
+    ) -> Response<Full<Bytes>> {
+        let resource = self.process(req, resource);
+        let accept = req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok());
+        let rendered = match self.negotiate(accept) {
+            Some((media, convert)) => convert(&resource).map(|body| {
+                let tag = canonical::etag(&body);
+                (*media, body, tag)
+            }),
+            None => to_string(&resource)
+                .map_err(HalError::Json)
+                .and_then(|body| Ok((HAL_CONTENT_TYPE, body, resource.etag()?))),
+        };
+        let (content_type, body, tag) = match rendered {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                let problem = Problem::from(e);
+                parts.status = StatusCode::from_u16(problem.status())
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                let body = to_string(&problem.to_resource()).unwrap_or_default();
+                (PROBLEM_CONTENT_TYPE, body, String::new())
+            }
+        };
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::ETAG);
+        if !self.formats.is_empty() {
+            parts.headers.append(header::VARY, HeaderValue::from_static("Accept"));
+        }
+        if self.etag && parts.status == StatusCode::OK && !tag.is_empty() {
+            if let Ok(value) = HeaderValue::from_str(&tag) {
+                parts.headers.insert(header::ETAG, value);
+            }
+            let if_none_match = req
+                .headers()
+                .get(header::IF_NONE_MATCH)
+                .and_then(|v| v.to_str().ok());
+            if if_none_match.is_some_and(|header| canonical::if_none_match(header, &tag)) {
+                parts.status = StatusCode::NOT_MODIFIED;
+                parts.headers.remove(header::CONTENT_TYPE);
+                return Response::from_parts(parts, Full::default());
+            }
+        }
+        parts
+            .headers
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        Response::from_parts(parts, Full::new(Bytes::from(body)))
+    }
+}
+
+impl<S> Layer<S> for HalLayer {
+    type Service = HalService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HalService {
+            inner,
+            layer: Arc::new(self.clone()),
+        }
+    }
+}
+
+/// The service `HalLayer` wraps services with.
+#[derive(Clone)]
+pub struct HalService<S> {
+    inner: S,
+    layer: Arc<HalLayer>,
+}
+
+fn is_hal(parts: &http1::response::Parts) -> bool {
+    let hal = HAL_CONTENT_TYPE.split(';').next().unwrap_or("");
+    parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case(hal))
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for HalService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<HalBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let mut snapshot = Request::new(());
+        *snapshot.method_mut() = req.method().clone();
+        *snapshot.uri_mut() = req.uri().clone();
+        *snapshot.version_mut() = req.version();
+        *snapshot.headers_mut() = req.headers().clone();
+        *snapshot.extensions_mut() = req.extensions().clone();
+
+        // The clone is ready, not `self.inner`: swap them.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let response = inner.call(req).await?;
+            let (parts, body) = response.into_parts();
+            if !is_hal(&parts) {
+                return Ok(Response::from_parts(parts, Either::Left(body)));
+            }
+            let bytes = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(e) => {
+                    let e: BoxError = e.into();
+                    let problem = Problem::from(HalError::Custom(e.to_string()));
+                    let response = Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .header(header::CONTENT_TYPE, PROBLEM_CONTENT_TYPE)
+                        .body(Full::new(Bytes::from(
+                            to_string(&problem.to_resource()).unwrap_or_default(),
+                        )))
+                        .unwrap_or_default();
+                    return Ok(response.map(Either::Right));
+                }
+            };
+            let response = match from_slice::<HalResource>(&bytes) {
+                Ok(resource) => layer.render(&snapshot, parts, resource),
+                Err(_) => Response::from_parts(parts, Full::new(bytes)),
+            };
+            Ok(response.map(Either::Right))
+        })
+    }
+}
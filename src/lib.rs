@@ -21,7 +21,6 @@
 //! extern crate rustic_hal;
 //! extern crate serde_json;
 //! extern crate serde;
-//! #[macro_use] extern crate serde_derive;
 //!
 //! use rustic_hal::*;
 //! use serde::Serialize;
@@ -41,18 +40,55 @@
 //! # }
 //! ```
 //!
+//! ## Round-trip fidelity
+//!
+//! Deserialized relations remember whether they were a single object or an
+//! array, and links whether they had an explicit `"templated": false`. With
+//! the `preserve_order` feature, top-level keys, relations and payload keys
+//! also keep their document (or insertion) order instead of being sorted.
+//!
+//! A compact document passed through unchanged then serializes exactly as it
+//! was read, with two limits: link properties are written in the order of
+//! the HAL specification (`href`, `templated`, `type`, …, then extensions),
+//! and numbers in the form `serde_json` prints them.
+//!
 //! ## Credits
 //!
 //! This library is heavily inspired by the [hal-rs](https://github.com/hjr3/hal-rs) library by Herman J. Radtke III.
 //!
+pub mod batch;
+pub mod canonical;
+pub mod deprecation;
+pub mod embed;
 pub mod error;
+pub mod fields;
+pub mod forwarded;
+pub mod identity;
+#[cfg(feature = "tower")]
+pub mod layer;
 pub mod link;
+pub mod link_array;
+pub mod link_header;
+pub mod link_policy;
 pub mod resource;
+pub mod reserved;
+pub mod response;
+pub mod route;
+pub mod sse;
+pub mod state_machine;
+pub mod uri;
+pub mod visitor;
 pub mod macros;
+pub mod patch;
+pub mod problem;
+pub mod rel;
 
+pub use self::deprecation::DeprecationObserver;
 pub use self::error::{HalError, HalResult};
 pub use self::link::HalLink;
+pub use self::rel::Rel;
 pub use self::resource::HalResource;
+pub use self::response::HalResponse;
 
 #[cfg(test)]
 mod tests;
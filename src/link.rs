@@ -1,5 +1,24 @@
 use std::convert::{From, Into};
-use serde::{Serialize, Deserialize};
+use std::hash::{Hash, Hasher};
+use std::time::SystemTime;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{from_value, to_value, Map, Value as JsonValue};
+
+use super::deprecation::{self, Deprecation};
+use super::identity;
+
+/// The properties HAL defines, which extensions cannot override.
+const PROPERTIES: &[&str] = &[
+    "href",
+    "templated",
+    "type",
+    "deprecation",
+    "name",
+    "profile",
+    "title",
+    "hreflang",
+];
 
 /// A Link object for linking HAL Resources.
 ///
@@ -13,7 +32,8 @@ use serde::{Serialize, Deserialize};
 ///
 /// let link = HalLink::new("http://sowewhere.com");
 /// ```
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "LinkRepr")]
 pub struct HalLink {
     /// The "href" property is REQUIRED.
     ///
@@ -30,13 +50,11 @@ pub struct HalLink {
     ///
     /// Its value SHOULD be considered false if it is undefined or any other
     /// value than true.
-    #[serde(skip_serializing_if = "is_not", default)]
     pub templated: bool,
     /// The "type" property is OPTIONAL.
     ///
     /// Its value is a string used as a hint to indicate the media type
     /// expected when dereferencing the target resource.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
     /// The "deprecation" property is OPTIONAL.
     ///
@@ -49,38 +67,102 @@ pub struct HalLink {
     /// property.  The notification SHOULD include the deprecation property's
     /// value so that a client manitainer can easily find information about
     /// the deprecation.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub deprecation: Option<String>,
 
     /// The "name" property is OPTIONAL.
     ///
     /// Its value MAY be used as a secondary key for selecting Link Objects
     /// which share the same relation type.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// The "profile" property is OPTIONAL.
     ///
     /// Its value is a string which is a URI that hints about the profile (as
     /// defined by [I-D.wilde-profile-link]) of the target resource.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub profile: Option<String>,
     /// The "title" property is OPTIONAL.
     ///
     /// Its value is a string and is intended for labelling the link with a
     /// human-readable identifier (as defined by [RFC5988]).
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     /// The "hreflang" property is OPTIONAL.
     ///
     /// Its value is a string and is intended for indicating the language of
     /// the target resource (as defined by [RFC5988]).
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub hreflang: Option<String>,
+
+    /// Any other property, such as `method` or `x-permission`.
+    ///
+    /// Unknown properties are kept here on deserialization, so that they
+    /// survive a round-trip.
+    pub extensions: Map<String, JsonValue>,
+
+    /// Whether the document the link was read from had a `templated`
+    /// property, written back even when `false`.
+    templated_present: bool,
+}
+
+/// A link as written in documents, telling whether `templated` is present.
+#[derive(Deserialize)]
+struct LinkRepr {
+    href: String,
+    templated: Option<bool>,
+    /// Read from `media_type` too, as written before it became `type`.
+    #[serde(rename = "type", alias = "media_type")]
+    media_type: Option<String>,
+    deprecation: Option<String>,
+    name: Option<String>,
+    profile: Option<String>,
+    title: Option<String>,
+    hreflang: Option<String>,
+    #[serde(flatten)]
+    extensions: Map<String, JsonValue>,
+}
+
+impl From<LinkRepr> for HalLink {
+    fn from(repr: LinkRepr) -> Self {
+        HalLink {
+            href: repr.href,
+            templated: repr.templated.unwrap_or(false),
+            media_type: repr.media_type,
+            deprecation: repr.deprecation,
+            name: repr.name,
+            profile: repr.profile,
+            title: repr.title,
+            hreflang: repr.hreflang,
+            extensions: repr.extensions,
+            templated_present: repr.templated.is_some(),
+        }
+    }
 }
 
-fn is_not(b: &bool) -> bool {
-    !*b
+/// Writes the properties in the order of the spec, `templated` when true or
+/// read from the document, then the extensions.
+impl Serialize for HalLink {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("href", &self.href)?;
+        if self.templated || self.templated_present {
+            map.serialize_entry("templated", &self.templated)?;
+        }
+        let properties = [
+            ("type", &self.media_type),
+            ("deprecation", &self.deprecation),
+            ("name", &self.name),
+            ("profile", &self.profile),
+            ("title", &self.title),
+            ("hreflang", &self.hreflang),
+        ];
+        for (name, value) in properties.iter() {
+            if let Some(value) = value {
+                map.serialize_entry(name, value)?;
+            }
+        }
+        for (name, value) in self.extensions.iter() {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
 }
 macro_rules! chainable_string {
     ($x: ident, $y: ident) => {
@@ -109,6 +191,8 @@ impl HalLink {
             profile: None,
             title: None,
             hreflang: None,
+            extensions: Map::new(),
+            templated_present: false,
         }
     }
 
@@ -123,6 +207,61 @@ impl HalLink {
     chainable_string!(profile, with_profile);
     chainable_string!(title, with_title);
     chainable_string!(hreflang, with_hreflang);
+
+    /// Set a non-standard property. Values that fail to serialize and
+    /// names of the standard properties are ignored.
+    pub fn with_extension<V: Serialize>(mut self, name: &str, value: V) -> Self {
+        if PROPERTIES.contains(&name) {
+            return self;
+        }
+        if let Ok(value) = to_value(value) {
+            self.extensions.insert(name.to_string(), value);
+        }
+        self
+    }
+
+    /// Mark the link as deprecated: `deprecation` is set to the notice's
+    /// info URL, and the sunset date and replacement relation go to the
+    /// `sunset` (as an HTTP-date) and `replacement` extensions.
+    pub fn with_deprecation_notice(mut self, notice: &Deprecation) -> Self {
+        if let Some(info) = notice.info() {
+            self.deprecation = Some(info.to_string());
+        }
+        if let Some(sunset) = notice.sunset() {
+            self = self.with_sunset(sunset);
+        }
+        if let Some(replacement) = notice.replacement() {
+            self = self.with_extension("replacement", replacement);
+        }
+        self
+    }
+
+    /// Set the `sunset` extension: when the link stops working. The date is
+    /// clamped to the years 1970 to 9999, which HTTP-dates can hold.
+    pub fn with_sunset(self, sunset: SystemTime) -> Self {
+        self.with_extension("sunset", deprecation::http_date(sunset))
+    }
+
+    /// The `sunset` extension, if it holds a valid HTTP-date.
+    pub fn sunset(&self) -> Option<SystemTime> {
+        self.extension::<String>("sunset")
+            .and_then(|date| httpdate::parse_http_date(&date).ok())
+    }
+
+    /// Whether the link's sunset date is past at `now`.
+    pub fn is_sunset(&self, now: SystemTime) -> bool {
+        self.sunset().is_some_and(|sunset| now >= sunset)
+    }
+
+    /// A non-standard property, if present and of type `V`.
+    pub fn extension<V>(&self, name: &str) -> Option<V>
+    where
+        for<'de> V: Deserialize<'de>,
+    {
+        self.extensions
+            .get(name)
+            .and_then(|value| from_value(value.clone()).ok())
+    }
 }
 
 impl<T> From<T> for HalLink
@@ -141,3 +280,40 @@ impl PartialEq for HalLink {
         self.href == other.href
     }
 }
+
+impl Eq for HalLink {}
+
+/// Hashes the href only, to agree with `PartialEq`.
+impl Hash for HalLink {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.href.hash(state);
+    }
+}
+
+impl HalLink {
+    /// Compare every property, not only the href.
+    pub fn structural_eq(&self, other: &HalLink) -> bool {
+        self.href == other.href
+            && self.templated == other.templated
+            && self.media_type == other.media_type
+            && self.deprecation == other.deprecation
+            && self.name == other.name
+            && self.profile == other.profile
+            && self.title == other.title
+            && self.hreflang == other.hreflang
+            && self.extensions == other.extensions
+    }
+
+    /// Hash every property, agreeing with `structural_eq`.
+    pub fn structural_hash<H: Hasher>(&self, state: &mut H) {
+        self.href.hash(state);
+        self.templated.hash(state);
+        self.media_type.hash(state);
+        self.deprecation.hash(state);
+        self.name.hash(state);
+        self.profile.hash(state);
+        self.title.hash(state);
+        self.hreflang.hash(state);
+        identity::hash_json(&JsonValue::Object(self.extensions.clone()), state);
+    }
+}
@@ -0,0 +1,89 @@
+//! Per-relation choice between the single-object and array forms.
+//!
+//! HAL lets a relation hold either one object or an array, and clients
+//! usually expect one form per relation: a collection of items should stay
+//! an array when it happens to hold a single item, and `self` should never
+//! be one. A `LinkArrayPolicy` set on a resource with
+//! `HalResource::with_link_array_policy` makes `with_link`, `with_resource`
+//! and `with_resources` follow those rules. Relations it says nothing about
+//! keep the usual behaviour: `with_resources` and `curies` make arrays, the
+//! others a single object until a second element is added.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::link_array::LinkArrayPolicy;
+//! use rustic_hal::HalResource;
+//! use serde_json::{json, to_value};
+//!
+//! let policy = LinkArrayPolicy::new().with_always_array(vec!["item"]);
+//! let res = HalResource::new(json!({}))
+//!     .with_link_array_policy(policy)
+//!     .with_link("self", "/orders")
+//!     .with_link("item", "/orders/1");
+//! assert_eq!(
+//!     to_value(&res).unwrap(),
+//!     json!({"_links": {"item": [{"href": "/orders/1"}], "self": {"href": "/orders"}}})
+//! );
+//! ```
+use std::collections::BTreeSet;
+
+/// Which relations always or never serialize as arrays.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinkArrayPolicy {
+    always: BTreeSet<String>,
+    never: BTreeSet<String>,
+    default: Option<bool>,
+}
+
+impl LinkArrayPolicy {
+    pub fn new() -> LinkArrayPolicy {
+        LinkArrayPolicy::default()
+    }
+
+    /// Always serialize `rels` as arrays.
+    pub fn with_always_array<I, S>(mut self, rels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for rel in rels {
+            let rel = rel.into();
+            self.never.remove(&rel);
+            self.always.insert(rel);
+        }
+        self
+    }
+
+    /// Serialize `rels` as a single object whenever they hold one element.
+    pub fn with_never_array<I, S>(mut self, rels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for rel in rels {
+            let rel = rel.into();
+            self.always.remove(&rel);
+            self.never.insert(rel);
+        }
+        self
+    }
+
+    /// The form of the relations not listed otherwise.
+    pub fn with_default_array(mut self, array: bool) -> Self {
+        self.default = Some(array);
+        self
+    }
+
+    /// Whether `rel` is an array under this policy, or `None` if the policy
+    /// does not say. `curies` are always arrays.
+    pub fn is_array(&self, rel: &str) -> Option<bool> {
+        if rel == "curies" || self.always.contains(rel) {
+            Some(true)
+        } else if self.never.contains(rel) {
+            Some(false)
+        } else {
+            self.default
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Conversion between HAL links and the HTTP `Link` header ([RFC 8288](https://tools.ietf.org/html/rfc8288)).
+//!
+//! The link's `title`, `type`, `hreflang`, `name`, `profile` and
+//! `deprecation` properties map to link parameters of the same name, and
+//! string extensions to link parameters of their own name.
+//! Templated links and curies have no meaning outside of HAL and are left out
+//! of the header.
+//!
+//! The header only holds visible ASCII: characters of hrefs and relations
+//! that cannot appear in a URI are percent-encoded, and parameters with other
+//! characters are written in the RFC 8187 form, as in
+//! `title*=UTF-8''Caf%C3%A9`, which `parse` reads back.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::{HalLink, HalResource};
+//!
+//! let res = HalResource::new(())
+//!     .with_link("next", HalLink::new("/orders/13").with_title("Next order"))
+//!     .with_link("self", "/orders/12");
+//! assert_eq!(
+//!     res.to_link_header(),
+//!     r#"</orders/13>; rel="next"; title="Next order", </orders/12>; rel="self""#
+//! );
+//!
+//! let res = HalResource::new(()).with_link_header(r#"</orders/12>; rel="self""#).unwrap();
+//! assert_eq!(res.get_self().unwrap().href, "/orders/12");
+//! ```
+use serde_json::Value as JsonValue;
+
+use super::link::HalLink;
+use super::{HalError, HalResult};
+
+fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Percent-encode the characters that cannot appear in a URI reference,
+/// leaving existing escapes alone.
+fn encode_uri(uri: &str) -> String {
+    let mut out = String::with_capacity(uri.len());
+    for c in uri.chars() {
+        match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' => out.push(c),
+            '-' | '.' | '_' | '~' | ':' | '/' | '?' | '#' | '[' | ']' | '@' | '!' | '$' | '&'
+            | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '=' | '%' => out.push(c),
+            c => {
+                let mut buf = [0; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("%{:02X}", b));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Whether `name` is a token, as link parameter names must be.
+fn is_token(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Write the parameter `name`, quoted if its value is visible ASCII, in
+/// the RFC 8187 `name*=UTF-8''...` form otherwise.
+fn push_param(out: &mut String, name: &str, value: &str) {
+    out.push_str("; ");
+    out.push_str(name);
+    if value.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+        out.push('=');
+        out.push_str(&quote(value));
+        return;
+    }
+    out.push_str("*=UTF-8''");
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+}
+
+/// Decode an RFC 8187 `charset'language'value`; only UTF-8 is supported.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?.as_bytes();
+    if !charset.eq_ignore_ascii_case("utf-8") {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        if encoded[i] == b'%' && i + 2 < encoded.len() {
+            let hex = std::str::from_utf8(&encoded[i + 1..i + 3]).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            bytes.push(encoded[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Format one link as a `Link` header field value.
+pub fn format_link(rel: &str, link: &HalLink) -> String {
+    let mut out = format!("<{}>; rel={}", encode_uri(&link.href), quote(&encode_uri(rel)));
+    let params = [
+        ("title", &link.title),
+        ("type", &link.media_type),
+        ("hreflang", &link.hreflang),
+        ("name", &link.name),
+        ("profile", &link.profile),
+        ("deprecation", &link.deprecation),
+    ];
+    for (name, value) in params.iter() {
+        if let Some(value) = value {
+            push_param(&mut out, name, value);
+        }
+    }
+    for (name, value) in link.extensions.iter() {
+        if let Some(value) = value.as_str() {
+            if is_token(name) && !name.ends_with('*') {
+                push_param(&mut out, name, value);
+            }
+        }
+    }
+    out
+}
+
+/// Format `(rel, link)` pairs as a `Link` header value, skipping templated links.
+pub fn format<'a, I>(links: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, &'a HalLink)>,
+{
+    links
+        .into_iter()
+        .filter(|(rel, link)| !link.templated && *rel != "curies")
+        .map(|(rel, link)| format_link(rel, link))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Split `s` on `sep`, ignoring separators inside `<...>` and quoted strings.
+fn split_outside(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_uri = false;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' if !in_uri => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_uri = true,
+            '>' if !in_quotes => in_uri = false,
+            c if c == sep && !in_uri && !in_quotes => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => {
+            let mut out = String::with_capacity(inner.len());
+            let mut escaped = false;
+            for c in inner.chars() {
+                if c == '\\' && !escaped {
+                    escaped = true;
+                } else {
+                    out.push(c);
+                    escaped = false;
+                }
+            }
+            out
+        }
+        None => value.to_string(),
+    }
+}
+
+/// Parse a `Link` header value into `(rel, link)` pairs.
+///
+/// A link with several relation types (`rel="next last"`) yields one pair
+/// per relation type; links without a `rel` parameter are ignored.
+pub fn parse(header: &str) -> HalResult<Vec<(String, HalLink)>> {
+    let mut links = Vec::new();
+    for value in split_outside(header, ',') {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let mut params = split_outside(value, ';').into_iter();
+        let target = params.next().unwrap_or("").trim();
+        let href = target
+            .strip_prefix('<')
+            .and_then(|t| t.strip_suffix('>'))
+            .ok_or_else(|| HalError::Custom(format!("Invalid link target {}", target)))?;
+
+        let mut link = HalLink::new(href);
+        let mut rels = String::new();
+        let mut extended = Vec::new();
+        for param in params {
+            let (name, value) = match param.find('=') {
+                Some(i) => (param[..i].trim(), unquote(&param[i + 1..])),
+                None => (param.trim(), String::new()),
+            };
+            // RFC 8187 values are applied last, so that they win over the
+            // plain ones.
+            if let Some(name) = name.strip_suffix('*') {
+                if let Some(value) = decode_ext_value(&value) {
+                    extended.push((name.to_string(), value));
+                }
+                continue;
+            }
+            set_param(&mut link, &mut rels, name, value);
+        }
+        for (name, value) in extended {
+            set_param(&mut link, &mut rels, &name, value);
+        }
+        for rel in rels.split_whitespace() {
+            links.push((rel.to_string(), link.clone()));
+        }
+    }
+    Ok(links)
+}
+
+/// Set the link parameter `name` on `link`, or the relations in `rels`.
+fn set_param(link: &mut HalLink, rels: &mut String, name: &str, value: String) {
+    match name.to_ascii_lowercase().as_str() {
+        // RFC 8288: occurrences after the first one are ignored.
+        "rel" if rels.is_empty() => *rels = value,
+        "rel" => {}
+        "title" => link.title = Some(value),
+        "type" => link.media_type = Some(value),
+        "hreflang" => link.hreflang = Some(value),
+        "name" => link.name = Some(value),
+        "profile" => link.profile = Some(value),
+        "deprecation" => link.deprecation = Some(value),
+        other => {
+            link.extensions.insert(other.to_string(), JsonValue::String(value));
+        }
+    }
+}
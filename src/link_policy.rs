@@ -0,0 +1,63 @@
+//! Filtering links by what the caller is allowed to do.
+//!
+//! A `LinkPolicy` looks at a request context (the authenticated user, their
+//! roles...) and every link of a resource, and decides to keep, drop or
+//! rewrite it. `HalResource::apply_link_policy` runs it over the resource
+//! and, recursively, its embedded resources, and
+//! `HalResponse::with_link_policy` does the same right before the response
+//! is rendered by any of the framework integrations.
+//!
+//! Any `Fn(&C, &str, &HalLink) -> LinkDecision` closure is a policy.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::link_policy::LinkDecision;
+//! use rustic_hal::{HalLink, HalResource};
+//! use serde_json::json;
+//!
+//! struct User {
+//!     id: u64,
+//! }
+//!
+//! let only_owner_cancels = |user: &User, rel: &str, _link: &HalLink| match rel {
+//!     "cancel" if user.id != 7 => LinkDecision::Drop,
+//!     _ => LinkDecision::Keep,
+//! };
+//!
+//! let order = HalResource::new(json!({"owner": 7}))
+//!     .with_link("self", "/orders/12")
+//!     .with_link("cancel", "/orders/12/cancel");
+//!
+//! let seen_by_other = order.clone().apply_link_policy(&only_owner_cancels, &User { id: 8 });
+//! assert!(seen_by_other.get_link("cancel").is_none());
+//! let seen_by_owner = order.apply_link_policy(&only_owner_cancels, &User { id: 7 });
+//! assert!(seen_by_owner.get_link("cancel").is_some());
+//! ```
+use super::link::HalLink;
+
+/// What to do with a link.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum LinkDecision {
+    Keep,
+    Drop,
+    /// Replace the link, for instance with a restricted variant.
+    Rewrite(HalLink),
+}
+
+/// Decides, for a request context `C`, which links a resource shows.
+pub trait LinkPolicy<C: ?Sized> {
+    /// Decide the fate of `link`, found under `rel` (`curies` included).
+    fn decide(&self, ctx: &C, rel: &str, link: &HalLink) -> LinkDecision;
+}
+
+impl<C, F> LinkPolicy<C> for F
+where
+    C: ?Sized,
+    F: Fn(&C, &str, &HalLink) -> LinkDecision,
+{
+    fn decide(&self, ctx: &C, rel: &str, link: &HalLink) -> LinkDecision {
+        self(ctx, rel, link)
+    }
+}
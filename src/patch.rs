@@ -0,0 +1,263 @@
+//! JSON Merge Patch ([RFC 7396](https://tools.ietf.org/html/rfc7396)) and
+//! JSON Patch ([RFC 6902](https://tools.ietf.org/html/rfc6902)) on resource
+//! payloads.
+//!
+//! Patches apply to the payload only: a patch touching the reserved
+//! `_links`, `_embedded` or `_curies` keys is rejected with
+//! `HalError::ReservedKey`, and a JSON Patch applies atomically.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::patch::{diff, PatchOp};
+//! use rustic_hal::HalResource;
+//! use serde_json::json;
+//!
+//! let before = HalResource::new(json!({"status": "pending", "total": 30}));
+//! let mut after = before.clone();
+//! after.apply_merge_patch(&json!({"status": "paid"})).unwrap();
+//!
+//! let ops = diff(&before, &after);
+//! assert_eq!(ops, vec![PatchOp::Replace { path: "/status".to_string(), value: json!("paid") }]);
+//!
+//! let mut replay = before.clone();
+//! replay.apply_json_patch(&ops).unwrap();
+//! assert!(replay.structural_eq(&after));
+//! ```
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+use super::reserved::{self, RESERVED_KEYS};
+use super::resource::HalResource;
+use super::{HalError, HalResult};
+
+/// A JSON Patch operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Add { path: String, value: JsonValue },
+    Remove { path: String },
+    Replace { path: String, value: JsonValue },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: JsonValue },
+}
+
+/// Split a JSON Pointer into unescaped reference tokens.
+fn tokens(pointer: &str) -> HalResult<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err(HalError::InvalidPatch(format!("Invalid pointer {}", pointer)));
+    }
+    Ok(pointer[1..]
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn check_reserved(pointer: &str) -> HalResult<()> {
+    match tokens(pointer)?.first() {
+        None => Err(HalError::InvalidPatch(
+            "Patches cannot replace the whole resource".to_string(),
+        )),
+        Some(key) if reserved::is_reserved(key) => {
+            Err(HalError::ReservedKey(key.clone()))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn array_index(token: &str, len: usize, allow_end: bool) -> HalResult<usize> {
+    if allow_end && token == "-" {
+        return Ok(len);
+    }
+    let valid = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit());
+    let valid = valid && (token == "0" || !token.starts_with('0'));
+    match token.parse::<usize>() {
+        Ok(i) if valid && (i < len || (allow_end && i == len)) => Ok(i),
+        _ => Err(HalError::InvalidPatch(format!("Invalid array index {}", token))),
+    }
+}
+
+fn get<'a>(value: &'a JsonValue, pointer: &str) -> HalResult<&'a JsonValue> {
+    value
+        .pointer(pointer)
+        .ok_or_else(|| HalError::InvalidPatch(format!("No value at {}", pointer)))
+}
+
+/// The parent container of `pointer` and the last token.
+fn parent<'a>(value: &'a mut JsonValue, pointer: &str) -> HalResult<(&'a mut JsonValue, String)> {
+    let mut tokens = tokens(pointer)?;
+    let last = tokens
+        .pop()
+        .ok_or_else(|| HalError::InvalidPatch("Empty pointer".to_string()))?;
+    let parent_pointer: String = tokens.iter().map(|t| format!("/{}", escape(t))).collect();
+    let parent = value
+        .pointer_mut(&parent_pointer)
+        .ok_or_else(|| HalError::InvalidPatch(format!("No value at {}", parent_pointer)))?;
+    Ok((parent, last))
+}
+
+fn add(value: &mut JsonValue, pointer: &str, new: JsonValue) -> HalResult<()> {
+    let (parent, last) = parent(value, pointer)?;
+    match parent {
+        JsonValue::Object(map) => {
+            map.insert(last, new);
+            Ok(())
+        }
+        JsonValue::Array(items) => {
+            let i = array_index(&last, items.len(), true)?;
+            items.insert(i, new);
+            Ok(())
+        }
+        _ => Err(HalError::InvalidPatch(format!("Cannot add at {}", pointer))),
+    }
+}
+
+fn remove(value: &mut JsonValue, pointer: &str) -> HalResult<JsonValue> {
+    let (parent, last) = parent(value, pointer)?;
+    let removed = match parent {
+        JsonValue::Object(map) => map.remove(&last),
+        JsonValue::Array(items) => {
+            let i = array_index(&last, items.len(), false)?;
+            Some(items.remove(i))
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| HalError::InvalidPatch(format!("No value at {}", pointer)))
+}
+
+/// Apply `ops` to `target`, stopping at the first failing operation.
+pub fn apply_json_patch(target: &mut JsonValue, ops: &[PatchOp]) -> HalResult<()> {
+    for op in ops {
+        match op {
+            PatchOp::Add { path, value } => add(target, path, value.clone())?,
+            PatchOp::Remove { path } => {
+                remove(target, path)?;
+            }
+            PatchOp::Replace { path, value } => {
+                remove(target, path)?;
+                add(target, path, value.clone())?;
+            }
+            PatchOp::Move { from, path } => {
+                if path.starts_with(&format!("{}/", from)) {
+                    return Err(HalError::InvalidPatch(format!(
+                        "Cannot move {} into itself",
+                        from
+                    )));
+                }
+                let value = remove(target, from)?;
+                add(target, path, value)?;
+            }
+            PatchOp::Copy { from, path } => {
+                let value = get(target, from)?.clone();
+                add(target, path, value)?;
+            }
+            PatchOp::Test { path, value } => {
+                if get(target, path)? != value {
+                    return Err(HalError::PatchConflict(format!(
+                        "Test failed at {}",
+                        path
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply a merge patch to `target`.
+pub fn apply_merge_patch(target: &mut JsonValue, patch: &JsonValue) {
+    match patch {
+        JsonValue::Object(patch) => {
+            if !target.is_object() {
+                *target = JsonValue::Object(Map::new());
+            }
+            if let JsonValue::Object(map) = target {
+                for (key, value) in patch {
+                    if value.is_null() {
+                        map.remove(key);
+                    } else {
+                        apply_merge_patch(map.entry(key.clone()).or_insert(JsonValue::Null), value);
+                    }
+                }
+            }
+        }
+        other => *target = other.clone(),
+    }
+}
+
+/// Check that `ops` leave the reserved keys alone.
+pub fn check_json_patch(ops: &[PatchOp]) -> HalResult<()> {
+    for op in ops {
+        match op {
+            PatchOp::Move { from, path } | PatchOp::Copy { from, path } => {
+                check_reserved(from)?;
+                check_reserved(path)?;
+            }
+            PatchOp::Add { path, .. }
+            | PatchOp::Remove { path }
+            | PatchOp::Replace { path, .. }
+            | PatchOp::Test { path, .. } => check_reserved(path)?,
+        }
+    }
+    Ok(())
+}
+
+/// Check that a merge patch is an object leaving the reserved keys alone.
+pub fn check_merge_patch(patch: &JsonValue) -> HalResult<()> {
+    match patch {
+        JsonValue::Object(map) => match RESERVED_KEYS.iter().find(|k| map.contains_key(**k)) {
+            Some(key) => Err(HalError::ReservedKey(key.to_string())),
+            None => Ok(()),
+        },
+        _ => Err(HalError::InvalidPatch(
+            "A merge patch on a resource must be an object".to_string(),
+        )),
+    }
+}
+
+fn diff_values(from: &JsonValue, to: &JsonValue, pointer: &str, ops: &mut Vec<PatchOp>) {
+    match (from, to) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            for (key, value) in a {
+                let path = format!("{}/{}", pointer, escape(key));
+                match b.get(key) {
+                    Some(other) => diff_values(value, other, &path, ops),
+                    None => ops.push(PatchOp::Remove { path }),
+                }
+            }
+            for (key, value) in b {
+                if !a.contains_key(key) {
+                    ops.push(PatchOp::Add {
+                        path: format!("{}/{}", pointer, escape(key)),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (a, b) if a != b => ops.push(PatchOp::Replace {
+            path: pointer.to_string(),
+            value: b.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// A JSON Patch turning the payload of `from` into the payload of `to`.
+///
+/// Links and embedded resources are not compared.
+pub fn diff(from: &HalResource, to: &HalResource) -> Vec<PatchOp> {
+    let empty = JsonValue::Object(Map::new());
+    let a = from.get_data::<JsonValue>().unwrap_or_else(|_| empty.clone());
+    let b = to.get_data::<JsonValue>().unwrap_or(empty);
+    let mut ops = Vec::new();
+    diff_values(&a, &b, "", &mut ops);
+    ops
+}
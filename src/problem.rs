@@ -0,0 +1,283 @@
+//! Error documents built on `HalResource`.
+//!
+//! Two formats are supported:
+//!
+//! * [`application/vnd.error+json`](https://github.com/blongden/vnd.error):
+//!   a HAL resource with a `message`, an optional `logref` and `path`, and
+//!   nested errors embedded under `errors`.
+//! * [`application/problem+json`](https://tools.ietf.org/html/rfc7807): the
+//!   RFC 7807 Problem Details members, plus HAL `_links`.
+//!
+//! Both render through `HalResponse`, so they can be returned from actix,
+//! axum, warp, Rocket and Poem handlers. A `HalError` is sent as a problem
+//! document, with its message as `detail` for client errors only.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::problem::Problem;
+//! use serde_json::{json, to_value};
+//!
+//! let problem = Problem::new(404)
+//!     .with_title("Order not found")
+//!     .with_detail("No order with id 12")
+//!     .with_help("https://docs.acme.com/errors/not-found");
+//!
+//! assert_eq!(
+//!     to_value(&problem.to_resource()).unwrap(),
+//!     json!({
+//!         "_links": {"help": {"href": "https://docs.acme.com/errors/not-found"}},
+//!         "title": "Order not found",
+//!         "detail": "No order with id 12",
+//!         "status": 404
+//!     })
+//! );
+//! ```
+use serde::Serialize;
+use serde_json::{to_value, Map, Value as JsonValue};
+
+use super::link::HalLink;
+use super::resource::HalResource;
+use super::response::HalResponse;
+use super::HalError;
+
+/// The media type of vnd.error documents.
+pub const VND_ERROR_CONTENT_TYPE: &str = "application/vnd.error+json";
+
+/// The media type of RFC 7807 documents.
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// An RFC 7807 Problem Details document.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    status: u16,
+    problem_type: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    instance: Option<String>,
+    extensions: Map<String, JsonValue>,
+    links: Vec<(String, HalLink)>,
+}
+
+impl Problem {
+    pub fn new(status: u16) -> Problem {
+        Problem {
+            status,
+            problem_type: None,
+            title: None,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// A URI identifying the problem type; `about:blank` when unset.
+    pub fn with_type(mut self, problem_type: &str) -> Self {
+        self.problem_type = Some(problem_type.to_string());
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_detail(mut self, detail: &str) -> Self {
+        self.detail = Some(detail.to_string());
+        self
+    }
+
+    /// A URI identifying this occurrence of the problem.
+    pub fn with_instance(mut self, instance: &str) -> Self {
+        self.instance = Some(instance.to_string());
+        self
+    }
+
+    /// Add an extension member.
+    pub fn with_extension<V: Serialize>(mut self, name: &str, value: V) -> Self {
+        if let Ok(value) = to_value(value) {
+            self.extensions.insert(name.to_string(), value);
+        }
+        self
+    }
+
+    pub fn with_link<S, L>(mut self, rel: S, link: L) -> Self
+    where
+        S: Into<String>,
+        L: Into<HalLink>,
+    {
+        self.links.push((rel.into(), link.into()));
+        self
+    }
+
+    /// Link to documentation about the problem.
+    pub fn with_help<L: Into<HalLink>>(self, link: L) -> Self {
+        self.with_link("help", link)
+    }
+
+    /// Link to the resource the problem is about.
+    pub fn with_describes<L: Into<HalLink>>(self, link: L) -> Self {
+        self.with_link("describes", link)
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn to_resource(&self) -> HalResource {
+        let mut data = self.extensions.clone();
+        let members = [
+            ("type", &self.problem_type),
+            ("title", &self.title),
+            ("detail", &self.detail),
+            ("instance", &self.instance),
+        ];
+        for (name, value) in members.iter() {
+            if let Some(value) = value {
+                data.insert(name.to_string(), JsonValue::String(value.clone()));
+            }
+        }
+        data.insert("status".to_string(), JsonValue::from(self.status));
+        self.links
+            .iter()
+            .fold(HalResource::new(data), |res, (rel, link)| {
+                res.with_link(rel.as_str(), link.clone())
+            })
+    }
+
+    pub fn to_response(&self) -> HalResponse {
+        HalResponse::new(self.to_resource())
+            .with_status(self.status)
+            .with_content_type(PROBLEM_CONTENT_TYPE)
+    }
+}
+
+/// Server errors only get their title: their message, such as a serde
+/// error, describes the service's internals, so it is logged instead.
+impl From<&HalError> for Problem {
+    fn from(error: &HalError) -> Self {
+        let status = error.status();
+        let problem = Problem::new(status).with_title(error.title());
+        if status >= 500 {
+            log::error!("{}", error);
+            problem
+        } else {
+            problem.with_detail(&error.to_string())
+        }
+    }
+}
+
+impl From<HalError> for Problem {
+    fn from(error: HalError) -> Self {
+        Problem::from(&error)
+    }
+}
+
+/// A vnd.error document, possibly holding nested errors.
+#[derive(Clone, Debug)]
+pub struct VndError {
+    status: u16,
+    message: String,
+    logref: Option<JsonValue>,
+    path: Option<String>,
+    links: Vec<(String, HalLink)>,
+    errors: Vec<VndError>,
+}
+
+impl VndError {
+    pub fn new<S: Into<String>>(message: S) -> VndError {
+        VndError {
+            status: 500,
+            message: message.into(),
+            logref: None,
+            path: None,
+            links: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Set the status code of the response, `500` by default.
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// An identifier for this occurrence, such as a log entry id.
+    pub fn with_logref<V: Serialize>(mut self, logref: V) -> Self {
+        self.logref = to_value(logref).ok();
+        self
+    }
+
+    /// A JSON Pointer to the field in the request the error is about.
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_link<S, L>(mut self, rel: S, link: L) -> Self
+    where
+        S: Into<String>,
+        L: Into<HalLink>,
+    {
+        self.links.push((rel.into(), link.into()));
+        self
+    }
+
+    /// Link to documentation about the error.
+    pub fn with_help<L: Into<HalLink>>(self, link: L) -> Self {
+        self.with_link("help", link)
+    }
+
+    /// Link to the resource the error is about.
+    pub fn with_describes<L: Into<HalLink>>(self, link: L) -> Self {
+        self.with_link("describes", link)
+    }
+
+    /// Embed a nested error.
+    pub fn with_error(mut self, error: VndError) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn to_resource(&self) -> HalResource {
+        let mut res = HalResource::new(()).with_extra_data("message", &self.message);
+        if let Some(ref logref) = self.logref {
+            res = res.with_extra_data("logref", logref);
+        }
+        if let Some(ref path) = self.path {
+            res = res.with_extra_data("path", path);
+        }
+        for (rel, link) in self.links.iter() {
+            res = res.with_link(rel.as_str(), link.clone());
+        }
+        if !self.errors.is_empty() {
+            res = res
+                .with_extra_data("total", self.errors.len())
+                .with_resources("errors", self.errors.iter().map(|e| e.to_resource()).collect());
+        }
+        res
+    }
+
+    pub fn to_response(&self) -> HalResponse {
+        HalResponse::new(self.to_resource())
+            .with_status(self.status)
+            .with_content_type(VND_ERROR_CONTENT_TYPE)
+    }
+}
+
+impl From<&HalError> for VndError {
+    fn from(error: &HalError) -> Self {
+        VndError::new(error.to_string()).with_status(error.status())
+    }
+}
+
+impl From<HalError> for VndError {
+    fn from(error: HalError) -> Self {
+        VndError::from(&error)
+    }
+}
@@ -0,0 +1,244 @@
+//! Link relation types.
+//!
+//! `Rel` names the relation of a link, either one of the relations registered
+//! in the [IANA Link Relations registry](https://www.iana.org/assignments/link-relations/link-relations.xhtml),
+//! or a custom one: a CURIE such as `acme:widgets`, an absolute URI, or any
+//! other string.
+//!
+//! Everything that accepts a relation name as a string also accepts a `Rel`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::rel::Rel;
+//! use rustic_hal::HalResource;
+//!
+//! let res = HalResource::new(())
+//!     .with_link(Rel::SELF, "/orders?page=2")
+//!     .with_link(Rel::NEXT, "/orders?page=3")
+//!     .with_link(Rel::curie("acme", "widgets"), "/widgets");
+//!
+//! assert_eq!(res.get_link(Rel::NEXT).unwrap().href, "/orders?page=3");
+//! assert!(res.get_link("acme:widgets").is_some());
+//! ```
+use std::borrow::Cow;
+use std::fmt;
+
+use super::{HalError, HalResult};
+
+/// A link relation type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Rel(Cow<'static, str>);
+
+macro_rules! registered_rels {
+    ($($name:ident => $value:expr),* $(,)?) => {
+        impl Rel {
+            $(
+                #[doc = concat!("The registered `", $value, "` relation.")]
+                pub const $name: Rel = Rel(Cow::Borrowed($value));
+            )*
+        }
+    };
+}
+
+registered_rels! {
+    ABOUT => "about",
+    ALTERNATE => "alternate",
+    AUTHOR => "author",
+    CANONICAL => "canonical",
+    COLLECTION => "collection",
+    CURRENT => "current",
+    DESCRIBEDBY => "describedby",
+    DESCRIBES => "describes",
+    DUPLICATE => "duplicate",
+    EDIT => "edit",
+    EDIT_FORM => "edit-form",
+    EDIT_MEDIA => "edit-media",
+    ENCLOSURE => "enclosure",
+    FIRST => "first",
+    HELP => "help",
+    INDEX => "index",
+    ITEM => "item",
+    LAST => "last",
+    LATEST_VERSION => "latest-version",
+    LICENSE => "license",
+    NEXT => "next",
+    PREDECESSOR_VERSION => "predecessor-version",
+    PREV => "prev",
+    PREVIEW => "preview",
+    PREVIOUS => "previous",
+    PROFILE => "profile",
+    RELATED => "related",
+    REPLIES => "replies",
+    SEARCH => "search",
+    SELF => "self",
+    SERVICE => "service",
+    START => "start",
+    STATUS => "status",
+    SUCCESSOR_VERSION => "successor-version",
+    SUNSET => "sunset",
+    TYPE => "type",
+    UP => "up",
+    VERSION_HISTORY => "version-history",
+    VIA => "via",
+    WORKING_COPY => "working-copy",
+    WORKING_COPY_OF => "working-copy-of",
+}
+
+/// Relation types from the IANA registry, plus the HAL `curies` relation.
+pub const REGISTERED: &[&str] = &[
+    "about", "acl", "alternate", "amphtml", "appendix", "apple-touch-icon",
+    "apple-touch-startup-image", "archives", "author", "blocked-by", "bookmark", "canonical",
+    "chapter", "cite-as", "collection", "contents", "convertedfrom", "copyright", "create-form",
+    "current", "curies", "describedby", "describes", "disclosure", "dns-prefetch", "duplicate",
+    "edit", "edit-form", "edit-media", "enclosure", "external", "first", "glossary", "help",
+    "hosts", "hub", "icon", "index", "intervalafter", "intervalbefore", "intervalcontains",
+    "intervaldisjoint", "intervalduring", "intervalequals", "intervalfinishedby",
+    "intervalfinishes", "intervalin", "intervalmeets", "intervalmetby", "intervaloverlappedby",
+    "intervaloverlaps", "intervalstartedby", "intervalstarts", "item", "last", "latest-version",
+    "license", "linkset", "lrdd", "manifest", "mask-icon", "me", "media-feed", "memento",
+    "micropub", "modulepreload", "monitor", "monitor-group", "next", "next-archive", "nofollow",
+    "noopener", "noreferrer", "opener", "openid2.local_id", "openid2.provider", "original",
+    "p3pv1", "payment", "pingback", "preconnect", "predecessor-version", "prefetch", "preload",
+    "prerender", "prev", "prev-archive", "preview", "previous", "privacy-policy", "profile",
+    "publication", "related", "replies", "restconf", "ruleinput", "search", "section", "self",
+    "service", "service-desc", "service-doc", "service-meta", "sibling", "sip-trunking-service",
+    "sponsored", "start", "status", "stylesheet", "subsection", "successor-version", "sunset",
+    "tag", "terms-of-service", "timegate", "timemap", "type", "ugc", "up", "version-history",
+    "via", "webmention", "working-copy", "working-copy-of",
+];
+
+impl Rel {
+    /// A custom relation type.
+    pub fn custom<S: Into<String>>(name: S) -> Rel {
+        Rel(Cow::Owned(name.into()))
+    }
+
+    /// A compact URI relation, such as `acme:widgets`.
+    pub fn curie(prefix: &str, reference: &str) -> Rel {
+        Rel(Cow::Owned(format!("{}:{}", prefix, reference)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this relation appears in the IANA registry.
+    pub fn is_registered(&self) -> bool {
+        is_registered(&self.0)
+    }
+
+    /// Whether this relation is a CURIE or an absolute URI.
+    pub fn is_extension(&self) -> bool {
+        self.0.contains(':')
+    }
+}
+
+impl fmt::Display for Rel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Rel {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Rel {
+    fn from(s: &str) -> Self {
+        Rel::custom(s)
+    }
+}
+
+impl From<String> for Rel {
+    fn from(s: String) -> Self {
+        Rel::custom(s)
+    }
+}
+
+impl From<Rel> for String {
+    fn from(rel: Rel) -> Self {
+        rel.0.into_owned()
+    }
+}
+
+impl From<&Rel> for String {
+    fn from(rel: &Rel) -> Self {
+        rel.0.to_string()
+    }
+}
+
+/// Whether `name` appears in the IANA registry (relation types are case-insensitive).
+pub fn is_registered(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    REGISTERED.contains(&lower.as_str())
+}
+
+/// The shortest name considered a possible typo: shorter names are one edit
+/// away from too many words.
+const MIN_TYPO_LEN: usize = 5;
+
+/// Common words one edit away from a registered relation, which are
+/// legitimate custom relations.
+const COMMON_WORDS: &[&str] = &[
+    "ghosts", "licensed", "opened", "posts", "relate", "reload", "replied", "smart", "stars",
+];
+
+/// The registered relation `name` was most likely meant to be, if it looks
+/// like a typo of one: one character inserted, deleted or replaced, or two
+/// adjacent ones swapped.
+///
+/// Registered relations, CURIEs and URIs are never considered typos, nor
+/// are names shorter than 5 characters, common words, and the plural or
+/// singular of a registered relation (such as `items` or `archive`).
+pub fn suggest(name: &str) -> Option<&'static str> {
+    let lower = name.to_ascii_lowercase();
+    if lower.chars().count() < MIN_TYPO_LEN
+        || is_registered(&lower)
+        || is_registered(&format!("{}s", lower))
+        || lower.contains(':')
+        || COMMON_WORDS.contains(&lower.as_str())
+    {
+        return None;
+    }
+    REGISTERED
+        .iter()
+        .find(|reg| lower != format!("{}s", reg) && distance(&lower, reg) == 1)
+        .copied()
+}
+
+/// Fails if `name` looks like a typo of a registered relation.
+pub fn check(name: &str) -> HalResult<()> {
+    match suggest(name) {
+        Some(reg) => Err(HalError::SuspiciousRel(name.to_string(), reg.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Edit distance counting insertions, deletions, substitutions and
+/// transpositions of adjacent characters.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,132 @@
+//! Payload keys colliding with the HAL structure.
+//!
+//! The payload of a resource is serialized next to `_links`, `_embedded` and
+//! `_curies`, so a payload key with one of those names would produce
+//! duplicated keys. HAL also reserves underscore-prefixed keys in general,
+//! though payloads commonly use some (such as `_id`). `HalResource::new`
+//! leaves the colliding keys out; with `HalResource::try_new`, a `KeyPolicy`
+//! decides what happens to such keys.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::reserved::KeyPolicy;
+//! use rustic_hal::{HalError, HalResource};
+//! use serde_json::{json, to_value};
+//!
+//! let payload = json!({"_links": "mine", "_id": 12});
+//! match HalResource::try_new(&payload, &KeyPolicy::reject()) {
+//!     Err(HalError::ReservedKey(key)) => assert_eq!(key, "_links"),
+//!     _ => unreachable!(),
+//! }
+//!
+//! let res = HalResource::try_new(&payload, &KeyPolicy::rename("x").with_underscored(true)).unwrap();
+//! assert_eq!(to_value(&res).unwrap(), json!({"x_id": 12, "x_links": "mine"}));
+//! ```
+use serde_json::{Map, Value as JsonValue};
+
+use super::{HalError, HalResult};
+
+/// Top-level keys that belong to the HAL structure rather than the payload.
+pub const RESERVED_KEYS: &[&str] = &["_links", "_embedded", "_curies"];
+
+/// Whether `key` is one of `RESERVED_KEYS`.
+pub fn is_reserved(key: &str) -> bool {
+    RESERVED_KEYS.contains(&key)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Action {
+    Reject,
+    Rename(String),
+    Drop,
+}
+
+/// What to do with a payload key colliding with the HAL structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyPolicy {
+    action: Action,
+    underscored: bool,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        KeyPolicy::reject()
+    }
+}
+
+impl KeyPolicy {
+    /// Fail with `HalError::ReservedKey`.
+    pub fn reject() -> KeyPolicy {
+        KeyPolicy {
+            action: Action::Reject,
+            underscored: false,
+        }
+    }
+
+    /// Keep the value under the key prefixed with `prefix`, so that
+    /// `_links` becomes `x_links` with a prefix of `x`.
+    pub fn rename(prefix: &str) -> KeyPolicy {
+        KeyPolicy {
+            action: Action::Rename(prefix.to_string()),
+            underscored: false,
+        }
+    }
+
+    /// Silently leave the key out.
+    pub fn drop() -> KeyPolicy {
+        KeyPolicy {
+            action: Action::Drop,
+            underscored: false,
+        }
+    }
+
+    /// Also apply the policy to any underscore-prefixed key, not only to
+    /// `RESERVED_KEYS`.
+    pub fn with_underscored(mut self, underscored: bool) -> Self {
+        self.underscored = underscored;
+        self
+    }
+
+    /// Whether this policy applies to `key`.
+    pub fn applies_to(&self, key: &str) -> bool {
+        is_reserved(key) || (self.underscored && key.starts_with('_'))
+    }
+
+    /// The key to store a payload `key` under, or `None` to leave it out.
+    pub fn key(&self, key: &str) -> HalResult<Option<String>> {
+        if !self.applies_to(key) {
+            return Ok(Some(key.to_string()));
+        }
+        match self.action {
+            Action::Reject => Err(HalError::ReservedKey(key.to_string())),
+            Action::Rename(ref prefix) => {
+                let renamed = format!("{}{}", prefix, key);
+                if self.applies_to(&renamed) {
+                    return Err(HalError::ReservedKey(renamed));
+                }
+                Ok(Some(renamed))
+            }
+            Action::Drop => Ok(None),
+        }
+    }
+
+    /// Apply the policy to every top-level key of `payload`.
+    pub fn apply(&self, payload: Map<String, JsonValue>) -> HalResult<Map<String, JsonValue>> {
+        let mut out = Map::new();
+        let renamed: Vec<_> = payload.keys().filter(|k| self.applies_to(k)).cloned().collect();
+        for key in renamed {
+            if let Some(to) = self.key(&key)? {
+                if payload.contains_key(&to) {
+                    return Err(HalError::ReservedKey(to));
+                }
+            }
+        }
+        for (key, value) in payload {
+            if let Some(key) = self.key(&key)? {
+                out.insert(key, value);
+            }
+        }
+        Ok(out)
+    }
+}
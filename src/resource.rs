@@ -1,11 +1,29 @@
+#[cfg(feature = "preserve_order")]
+use indexmap::map::Entry;
+#[cfg(not(feature = "preserve_order"))]
 use std::collections::btree_map::Entry;
-use std::collections::*;
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 use std::vec::*;
 
 use serde::de::Error;
+use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use super::canonical;
+use super::deprecation::{self, DeprecationObserver};
+use super::fields::{self, Fieldset};
+use super::identity::{self, Identified, Identity};
 use super::link::HalLink;
+use super::link_array::LinkArrayPolicy;
+use super::link_header;
+use super::link_policy::{LinkDecision, LinkPolicy};
+use super::patch::{self, PatchOp};
+use super::rel;
+use super::reserved::{self, KeyPolicy};
+use super::uri;
+use super::visitor::{HalVisitor, HalVisitorMut, HrefRewriter};
 use super::{HalError, HalResult};
 use serde_json::{from_value, to_value, Map, Value as JsonValue};
 
@@ -65,6 +83,11 @@ where
         self
     }
 
+    /// Whether this serializes as an array, even with one element.
+    pub fn is_many(&self) -> bool {
+        self.force_many
+    }
+
     /// retrieve the length of the wrapped vector
     pub fn len(&self) -> usize {
         self.content.len()
@@ -90,6 +113,12 @@ where
         &self.content
     }
 
+    /// Returns a mutable reference to the
+    /// contained links
+    pub fn many_mut(&mut self) -> &mut Vec<T> {
+        &mut self.content
+    }
+
     /// Add an element to the wrapped vector.
     pub fn push(&mut self, newval: &T) {
         self.content.push(newval.clone());
@@ -147,7 +176,7 @@ where
                     Ok(v) => from_value(v).unwrap(),
                     Err(e) => return Err(D::Error::custom(format!("JSON Error: {:?}", e))),
                 };
-                let mut res = OneOrMany::new();
+                let mut res = OneOrMany::new().force_many();
                 res.content = obj;
                 Ok(res)
             }
@@ -164,54 +193,181 @@ where
     }
 }
 
-/// The HAL Resource structure.
+/// Relations keyed by name: sorted by default, kept in insertion (or
+/// document) order with the `preserve_order` feature.
+#[cfg(not(feature = "preserve_order"))]
+type RelMap<V> = BTreeMap<String, V>;
+#[cfg(feature = "preserve_order")]
+type RelMap<V> = indexmap::IndexMap<String, V>;
+
+/// The entries of `map` sorted by name, whatever its order.
+fn sorted<V>(map: &RelMap<V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
 
-#[derive(Clone, Serialize, Deserialize)]
+/// The HAL Resource structure.
+///
+/// It serializes to `_links`, `_embedded`, `_curies` and then the payload
+/// keys. With the `preserve_order` feature, a deserialized resource writes
+/// its top-level keys back in the order of the document instead.
+#[derive(Clone)]
 pub struct HalResource {
-    #[serde(rename = "_links", default, skip_serializing_if = "BTreeMap::is_empty")]
     /// Map of links to related resources.
-    links: BTreeMap<String, OneOrMany<HalLink>>,
+    links: RelMap<OneOrMany<HalLink>>,
 
-    #[serde(
-        rename = "_embedded",
-        default,
-        skip_serializing_if = "BTreeMap::is_empty"
-    )]
     /// Map of set of embedded resources.
-    embedded: BTreeMap<String, OneOrMany<HalResource>>,
+    embedded: RelMap<OneOrMany<HalResource>>,
 
-    #[serde(
-        rename = "_curies",
-        default,
-        skip_serializing_if = "BTreeMap::is_empty"
-    )]
     /// Documentations Curies
-    curies: BTreeMap<String, HalLink>,
+    curies: RelMap<HalLink>,
 
-    #[serde(flatten)]
     /// The actual resource data
     data: Option<JsonValue>,
+
+    /// Which relations are arrays
+    link_arrays: Option<LinkArrayPolicy>,
+
+    /// The top-level keys of the document the resource was read from.
+    #[cfg(feature = "preserve_order")]
+    key_order: Vec<String>,
+}
+
+fn from_data(data: Option<JsonValue>) -> HalResource {
+    HalResource {
+        links: RelMap::new(),
+        embedded: RelMap::new(),
+        curies: RelMap::new(),
+        data,
+        link_arrays: None,
+        #[cfg(feature = "preserve_order")]
+        key_order: Vec::new(),
+    }
+}
+
+impl HalResource {
+    /// The top-level keys to write: those of the document the resource was
+    /// read from still present, in order, then the others.
+    fn keys(&self) -> Vec<&str> {
+        let payload = self.data.as_ref().and_then(JsonValue::as_object);
+        let present = |key: &str| match key {
+            "_links" => !self.links.is_empty(),
+            "_embedded" => !self.embedded.is_empty(),
+            "_curies" => !self.curies.is_empty(),
+            key => payload.is_some_and(|data| data.contains_key(key)),
+        };
+        let defaults = reserved::RESERVED_KEYS
+            .iter()
+            .copied()
+            .chain(payload.into_iter().flat_map(|data| data.keys().map(String::as_str)));
+        #[cfg(feature = "preserve_order")]
+        {
+            let mut seen = std::collections::HashSet::new();
+            self.key_order
+                .iter()
+                .map(String::as_str)
+                .chain(defaults)
+                .filter(|key| present(key) && seen.insert(*key))
+                .collect()
+        }
+        #[cfg(not(feature = "preserve_order"))]
+        defaults.filter(|key| present(key)).collect()
+    }
+}
+
+impl Serialize for HalResource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let payload = self.data.as_ref().and_then(JsonValue::as_object);
+        let mut map = serializer.serialize_map(None)?;
+        for key in self.keys() {
+            match key {
+                "_links" => map.serialize_entry(key, &self.links)?,
+                "_embedded" => map.serialize_entry(key, &self.embedded)?,
+                "_curies" => map.serialize_entry(key, &self.curies)?,
+                key => {
+                    if let Some(value) = payload.and_then(|data| data.get(key)) {
+                        map.serialize_entry(key, value)?;
+                    }
+                }
+            }
+        }
+        map.end()
+    }
+}
+
+/// Reads the HAL sections, and everything else as the payload.
+impl<'de> Deserialize<'de> for HalResource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = Map::<String, JsonValue>::deserialize(deserializer)?;
+        let mut resource = from_data(None);
+        let mut data = Map::new();
+        for (key, value) in document {
+            #[cfg(feature = "preserve_order")]
+            resource.key_order.push(key.clone());
+            match key.as_str() {
+                "_links" => resource.links = from_value(value).map_err(D::Error::custom)?,
+                "_embedded" => resource.embedded = from_value(value).map_err(D::Error::custom)?,
+                "_curies" => resource.curies = from_value(value).map_err(D::Error::custom)?,
+                _ => {
+                    data.insert(key, value);
+                }
+            }
+        }
+        resource.data = Some(JsonValue::Object(data));
+        Ok(resource)
+    }
 }
 
 impl HalResource {
+    /// Create a resource with `payload`, which is dropped unless it
+    /// serializes to an object.
+    ///
+    /// Payload keys colliding with the HAL structure (`_links`, `_embedded`
+    /// and `_curies`) are left out, as when deserializing; use `try_new` to
+    /// reject or rename them instead.
     pub fn new<T>(payload: T) -> HalResource
+    where
+        T: Serialize,
+    {
+        let data = match to_value(payload) {
+            Ok(JsonValue::Object(mut map)) => {
+                map.retain(|key, _| !reserved::is_reserved(key));
+                Some(JsonValue::Object(map))
+            }
+            _ => None,
+        };
+        from_data(data)
+    }
+
+    /// Create a resource with `payload`, applying `policy` to the payload
+    /// keys colliding with the HAL structure.
+    pub fn try_new<T>(payload: T, policy: &KeyPolicy) -> HalResult<HalResource>
     where
         T: Serialize,
     {
         let val = match to_value(payload) {
-            Ok(val) => match val {
-                JsonValue::Object(_) => Some(val),
-                _ => None,
-            },
+            Ok(JsonValue::Object(map)) => Some(JsonValue::Object(policy.apply(map)?)),
             _ => None,
         };
+        Ok(from_data(val))
+    }
 
-        HalResource {
-            links: BTreeMap::new(),
-            embedded: BTreeMap::new(),
-            curies: BTreeMap::new(),
-            data: val,
+    /// Follow `policy` for the form of relations, from now on and for the
+    /// relations already there.
+    pub fn with_link_array_policy(mut self, policy: LinkArrayPolicy) -> Self {
+        for (rel, links) in self.links.iter_mut() {
+            links.force_many = policy.is_array(rel).unwrap_or(links.force_many);
+        }
+        for (rel, resources) in self.embedded.iter_mut() {
+            resources.force_many = policy.is_array(rel).unwrap_or(resources.force_many);
         }
+        self.link_arrays = Some(policy);
+        self
+    }
+
+    fn is_array(&self, rel: &str) -> Option<bool> {
+        self.link_arrays.as_ref().and_then(|p| p.is_array(rel))
     }
 
     pub fn with_link<S, L>(mut self, name: S, link: L) -> Self
@@ -220,14 +376,11 @@ impl HalResource {
         L: Into<HalLink>,
     {
         let lk_name = name.into();
-        match self.links.entry(lk_name.clone()) {
+        let array = self.is_array(&lk_name).unwrap_or(lk_name == "curies");
+        match self.links.entry(lk_name) {
             Entry::Vacant(entry) => {
-                let lk = OneOrMany::new();
-
-                let mut lk = match lk_name.as_ref() {
-                    "curies" => lk.force_many(),
-                    _ => lk,
-                };
+                let mut lk = OneOrMany::new();
+                lk.force_many = array;
 
                 lk.push(&(link.into()));
                 entry.insert(lk);
@@ -241,10 +394,47 @@ impl HalResource {
     }
 
     /// Retrieve one named link if found. Returns the first one if more than one.
-    pub fn get_link(&self, name: &str) -> Option<&HalLink> {
-        match self.links.get(name) {
-            Some(link) => link.single(),
-            None => None,
+    ///
+    /// A deprecated link is reported to the installed `DeprecationObserver`;
+    /// if the observer rejects it, the error is logged and the link is still
+    /// returned. Use `follow` to fail instead.
+    pub fn get_link<R: AsRef<str>>(&self, name: R) -> Option<&HalLink> {
+        let name = name.as_ref();
+        let link = self.links.get(name).and_then(|l| l.single());
+        if let Some(lk) = link {
+            if let Err(e) = deprecation::check(name, lk) {
+                log::error!("{}", e);
+            }
+        }
+        link
+    }
+
+    /// Retrieve the named link to traverse it, failing if it is missing or
+    /// if the installed `DeprecationObserver` rejects it.
+    pub fn follow<R: AsRef<str>>(&self, name: R) -> HalResult<&HalLink> {
+        let name = name.as_ref();
+        let link = self.find_link(name)?;
+        deprecation::check(name, link)?;
+        Ok(link)
+    }
+
+    /// Same as `follow`, but reports deprecation to `observer` instead of the
+    /// installed one.
+    pub fn follow_with<R: AsRef<str>>(
+        &self,
+        name: R,
+        observer: &dyn DeprecationObserver,
+    ) -> HalResult<&HalLink> {
+        let name = name.as_ref();
+        let link = self.find_link(name)?;
+        deprecation::check_with(observer, name, link)?;
+        Ok(link)
+    }
+
+    fn find_link(&self, name: &str) -> HalResult<&HalLink> {
+        match self.links.get(name).and_then(|l| l.single()) {
+            Some(link) => Ok(link),
+            None => Err(HalError::Custom(format!("Link {} missing", name))),
         }
     }
 
@@ -254,17 +444,25 @@ impl HalResource {
     }
 
     /// Retrieve the list of links for a key
-    pub fn get_links(&self, name: &str) -> Option<&Vec<HalLink>> {
-        match self.links.get(name) {
-            Some(link) => Some(link.many()),
-            None => None,
+    ///
+    /// Deprecated links are reported as in `get_link`.
+    pub fn get_links<R: AsRef<str>>(&self, name: R) -> Option<&Vec<HalLink>> {
+        let name = name.as_ref();
+        let links = self.links.get(name).map(|l| l.many());
+        for lk in links.into_iter().flatten() {
+            if let Err(e) = deprecation::check(name, lk) {
+                log::error!("{}", e);
+            }
         }
+        links
     }
 
     pub fn with_resource(mut self, name: &str, resource: HalResource) -> Self {
+        let array = self.is_array(name).unwrap_or(false);
         match self.embedded.entry(name.to_string()) {
             Entry::Vacant(entry) => {
                 let mut resources = OneOrMany::new();
+                resources.force_many = array;
                 resources.push(&resource);
                 entry.insert(resources);
             }
@@ -276,10 +474,27 @@ impl HalResource {
         self
     }
 
+    /// Retrieve one embedded resource if found. Returns the first one if more than one.
+    pub fn get_resource<R: AsRef<str>>(&self, name: R) -> Option<&HalResource> {
+        self.embedded.get(name.as_ref()).and_then(|r| r.single())
+    }
+
+    /// Retrieve the list of embedded resources for a key
+    pub fn get_resources<R: AsRef<str>>(&self, name: R) -> Option<&Vec<HalResource>> {
+        self.embedded.get(name.as_ref()).map(|r| r.many())
+    }
+
+    /// Retrieve the list of embedded resources for a key, to modify them
+    pub fn get_resources_mut<R: AsRef<str>>(&mut self, name: R) -> Option<&mut Vec<HalResource>> {
+        self.embedded.get_mut(name.as_ref()).map(|r| r.many_mut())
+    }
+
     pub fn with_resources(mut self, name: &str, resources: Vec<HalResource>) -> Self {
+        let array = self.is_array(name).unwrap_or(true);
         match self.embedded.entry(name.to_string()) {
             Entry::Vacant(entry) => {
-                let mut _resources = OneOrMany::new().force_many();
+                let mut _resources = OneOrMany::new();
+                _resources.force_many = array;
 
                 for resource in resources.iter() {
                     _resources.push(resource)
@@ -297,25 +512,272 @@ impl HalResource {
         self
     }
 
+    /// Check every link and embedded relation, recursively, for likely typos
+    /// of registered relations such as `colection`.
+    pub fn check_rels(&self) -> HalResult<()> {
+        for name in self.links.keys().chain(self.embedded.keys()) {
+            rel::check(name)?;
+        }
+        for resources in self.embedded.values() {
+            for resource in resources.many() {
+                resource.check_rels()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve every relative href, recursively through embedded resources,
+    /// against `base`, which should be an absolute URL.
+    ///
+    /// ```rust
+    /// use rustic_hal::HalResource;
+    ///
+    /// let res = HalResource::new(())
+    ///     .with_link("self", "12")
+    ///     .resolve_against("https://api.com/orders/");
+    /// assert_eq!(res.get_self().unwrap().href, "https://api.com/orders/12");
+    /// ```
+    pub fn resolve_against(self, base: &str) -> Self {
+        self.rewrite_hrefs(|href| uri::resolve(base, href))
+    }
+
+    /// Turn every href, recursively through embedded resources, into the
+    /// shortest reference that resolves back to it against `base`.
+    pub fn relativize(self, base: &str) -> Self {
+        self.rewrite_hrefs(|href| uri::relativize(base, href))
+    }
+
+    /// Replace every href, recursively through curies and embedded resources,
+    /// with the result of `rewrite`.
+    pub fn rewrite_hrefs<F>(mut self, rewrite: F) -> Self
+    where
+        F: FnMut(&str) -> String,
+    {
+        self.accept_mut(&mut HrefRewriter(rewrite));
+        self
+    }
+
+    /// Keep only the payload fields selected by `fieldset`, recursively
+    /// through embedded resources, and strip non-essential links if asked to.
+    pub fn project(mut self, fieldset: &Fieldset) -> Self {
+        self.project_at(fieldset, "");
+        self
+    }
+
+    fn project_at(&mut self, fieldset: &Fieldset, path: &str) {
+        if let (Some(fields), Some(JsonValue::Object(ref mut data))) =
+            (fieldset.fields(path), self.data.as_mut())
+        {
+            data.retain(|key, _| fields.contains(key));
+        }
+        if fieldset.strips_links() {
+            self.links
+                .retain(|rel, _| fields::ESSENTIAL_RELS.contains(&rel.as_str()));
+        }
+        for (rel, resources) in self.embedded.iter_mut() {
+            let nested = if path.is_empty() {
+                rel.clone()
+            } else {
+                format!("{}.{}", path, rel)
+            };
+            for resource in resources.many_mut() {
+                resource.project_at(fieldset, &nested);
+            }
+        }
+    }
+
+    /// Keep, drop or rewrite every link according to `policy`, recursively
+    /// through embedded resources. Relations left without links are removed.
+    pub fn apply_link_policy<C, P>(mut self, policy: &P, ctx: &C) -> Self
+    where
+        C: ?Sized,
+        P: LinkPolicy<C> + ?Sized,
+    {
+        self.apply_link_policy_mut(policy, ctx);
+        self
+    }
+
+    pub(crate) fn apply_link_policy_mut<C, P>(&mut self, policy: &P, ctx: &C)
+    where
+        C: ?Sized,
+        P: LinkPolicy<C> + ?Sized,
+    {
+        for (rel, links) in self.links.iter_mut() {
+            let kept = std::mem::take(links.many_mut())
+                .into_iter()
+                .filter_map(|link| match policy.decide(ctx, rel, &link) {
+                    LinkDecision::Keep => Some(link),
+                    LinkDecision::Drop => None,
+                    LinkDecision::Rewrite(link) => Some(link),
+                })
+                .collect();
+            *links.many_mut() = kept;
+        }
+        self.links.retain(|_, links| !links.is_empty());
+        self.curies = std::mem::take(&mut self.curies)
+            .into_iter()
+            .filter_map(|(name, link)| match policy.decide(ctx, "curies", &link) {
+                LinkDecision::Keep => Some((name, link)),
+                LinkDecision::Drop => None,
+                LinkDecision::Rewrite(link) => Some((name, link)),
+            })
+            .collect();
+        for resources in self.embedded.values_mut() {
+            for resource in resources.many_mut() {
+                resource.apply_link_policy_mut(policy, ctx);
+            }
+        }
+    }
+
+    /// Apply an RFC 7396 merge patch to the payload.
+    ///
+    /// Patches touching `_links`, `_embedded` or `_curies` are rejected.
+    pub fn apply_merge_patch(&mut self, patch: &JsonValue) -> HalResult<()> {
+        patch::check_merge_patch(patch)?;
+        let data = self.data.get_or_insert_with(|| JsonValue::Object(Map::new()));
+        patch::apply_merge_patch(data, patch);
+        Ok(())
+    }
+
+    /// Apply an RFC 6902 JSON Patch to the payload, atomically.
+    ///
+    /// Patches touching `_links`, `_embedded` or `_curies` are rejected.
+    pub fn apply_json_patch(&mut self, ops: &[PatchOp]) -> HalResult<()> {
+        patch::check_json_patch(ops)?;
+        let mut data = match self.data {
+            Some(ref data) => data.clone(),
+            None => JsonValue::Object(Map::new()),
+        };
+        patch::apply_json_patch(&mut data, ops)?;
+        self.data = Some(data);
+        Ok(())
+    }
+
+    /// The canonical JSON form of this resource, see the `canonical` module.
+    pub fn to_canonical_string(&self) -> HalResult<String> {
+        canonical::to_canonical_string(&to_value(self)?)
+    }
+
+    /// A weak ETag computed from the canonical form of this resource.
+    pub fn etag(&self) -> HalResult<String> {
+        Ok(canonical::weak_etag(&self.to_canonical_string()?))
+    }
+
+    /// Format the links of this resource as an RFC 8288 `Link` header value.
+    ///
+    /// Embedded resources, curies and templated links are left out.
+    pub fn to_link_header(&self) -> String {
+        link_header::format(
+            self.links
+                .iter()
+                .flat_map(|(rel, links)| links.many().iter().map(move |l| (rel.as_str(), l))),
+        )
+    }
+
+    /// Add the links of an RFC 8288 `Link` header value to this resource.
+    pub fn with_link_header(self, header: &str) -> HalResult<Self> {
+        Ok(link_header::parse(header)?
+            .into_iter()
+            .fold(self, |res, (rel, link)| res.with_link(rel, link)))
+    }
+
+    /// Walk this resource, its links and its embedded resources depth first.
+    pub fn accept<V>(&self, visitor: &mut V)
+    where
+        V: HalVisitor + ?Sized,
+    {
+        visitor.visit_resource(self);
+        for (rel, links) in self.links.iter() {
+            for link in links.many() {
+                visitor.visit_link(rel, link);
+            }
+        }
+        for link in self.curies.values() {
+            visitor.visit_link("curies", link);
+        }
+        for resources in self.embedded.values() {
+            for resource in resources.many() {
+                resource.accept(visitor);
+            }
+        }
+    }
+
+    /// Same as `accept`, allowing the visitor to modify what it visits.
+    pub fn accept_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: HalVisitorMut + ?Sized,
+    {
+        visitor.visit_resource_mut(self);
+        for (rel, links) in self.links.iter_mut() {
+            for link in links.many_mut() {
+                visitor.visit_link_mut(rel, link);
+            }
+        }
+        for link in self.curies.values_mut() {
+            visitor.visit_link_mut("curies", link);
+        }
+        for resources in self.embedded.values_mut() {
+            for resource in resources.many_mut() {
+                resource.accept_mut(visitor);
+            }
+        }
+    }
+
     pub fn with_curie(self, name: &str, href: &str) -> Self {
         self.with_link("curies", HalLink::new(href).templated(true).with_name(name))
     }
 
+    /// Add a payload field.
+    ///
+    /// A field named `_links`, `_embedded` or `_curies`, or whose value fails
+    /// to serialize, is left out and logged; use `try_with_extra_data` to
+    /// handle those.
     pub fn with_extra_data<V>(mut self, name: &str, value: V) -> Self
     where
         V: Serialize,
     {
+        if reserved::is_reserved(name) {
+            log::warn!("Reserved payload field {} left out", name);
+            return self;
+        }
+        match to_value(value) {
+            Ok(value) => self.insert_data(name.to_string(), value),
+            Err(e) => log::error!("Payload field {} left out: {}", name, e),
+        }
+        self
+    }
+
+    /// Add a payload field, applying `policy` if `name` collides with the
+    /// HAL structure.
+    pub fn try_with_extra_data<V>(
+        mut self,
+        name: &str,
+        value: V,
+        policy: &KeyPolicy,
+    ) -> HalResult<Self>
+    where
+        V: Serialize,
+    {
+        let name = match policy.key(name)? {
+            Some(name) => name,
+            None => return Ok(self),
+        };
+        let value = to_value(value)?;
+        self.insert_data(name, value);
+        Ok(self)
+    }
+
+    fn insert_data(&mut self, name: String, value: JsonValue) {
         match self.data {
             Some(JsonValue::Object(ref mut m)) => {
-                m.insert(name.to_string(), to_value(value).unwrap());
+                m.insert(name, value);
             }
             _ => {
                 let mut data = Map::<String, JsonValue>::new();
-                data.insert(name.to_string(), to_value(value).unwrap());
+                data.insert(name, value);
                 self.data = Some(JsonValue::Object(data));
             }
         };
-        self
     }
 
     pub fn get_extra_data<V>(&self, name: &str) -> HalResult<V>
@@ -343,107 +805,108 @@ impl HalResource {
     }
 }
 
-impl PartialEq for HalResource {
-    fn eq(&self, other: &HalResource) -> bool {
-        self.get_self() == other.get_self()
+impl HalResource {
+    pub(crate) fn self_href(&self) -> Option<&str> {
+        self.links
+            .get("self")
+            .and_then(|l| l.single())
+            .map(|l| l.href.as_str())
     }
-}
 
-#[cfg(feature = "actix-web")]
-mod actix {
-
-    use super::HalResource;
-    use actix_web::{HttpRequest, HttpResponse, Responder};
-    use actix_web::body::BoxBody;
-
-    impl Responder for HalResource {
-        type Body = BoxBody;
-        //type Error = Error;
-        //type Future = Ready<Result<HttpResponse, Error>>;
-
-        fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
-            let body = serde_json::to_string(&self).unwrap();
-
-            // Create response and set content type
-            HttpResponse::Ok()
-                .content_type("application/hal+json; charset=utf-8")
-                .body(body)
+    /// Compare payload, links and embedded resources, recursively.
+    ///
+    /// Whether a relation serializes as an object or an array is ignored.
+    pub fn structural_eq(&self, other: &HalResource) -> bool {
+        fn links_eq(a: &[HalLink], b: &[HalLink]) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.structural_eq(y))
+        }
+        fn map_eq<V>(a: &RelMap<V>, b: &RelMap<V>, eq: impl Fn(&V, &V) -> bool) -> bool {
+            a.len() == b.len() && a.iter().all(|(k, x)| b.get(k).is_some_and(|y| eq(x, y)))
         }
+        self.data == other.data
+            && map_eq(&self.links, &other.links, |a, b| links_eq(a.many(), b.many()))
+            && map_eq(&self.curies, &other.curies, |a, b| a.structural_eq(b))
+            && map_eq(&self.embedded, &other.embedded, |a, b| {
+                a.len() == b.len() && a.many().iter().zip(b.many()).all(|(x, y)| x.structural_eq(y))
+            })
     }
 
-    impl From<HalResource> for HttpResponse {
-        fn from(resource: HalResource) -> Self {
-            if let Ok(res) = serde_json::to_string(&resource) {
-                HttpResponse::Ok()
-                    .content_type("application/hal+json; charset=utf-8")
-                    .body(res)
-            } else {
-                HttpResponse::InternalServerError()
-                    .content_type("application/hal+json; charset=utf-8")
-                    .body("{\"error\":\"Internal Server Error\"}")
-            }
+    /// Hash payload, links and embedded resources, agreeing with `structural_eq`.
+    pub fn structural_hash<H: Hasher>(&self, state: &mut H) {
+        if let Some(ref data) = self.data {
+            identity::hash_json(data, state);
+        }
+        for (rel, links) in sorted(&self.links) {
+            rel.hash(state);
+            links.len().hash(state);
+            links.many().iter().for_each(|l| l.structural_hash(state));
+        }
+        for (name, curie) in sorted(&self.curies) {
+            name.hash(state);
+            curie.structural_hash(state);
+        }
+        for (rel, resources) in sorted(&self.embedded) {
+            rel.hash(state);
+            resources.len().hash(state);
+            resources.many().iter().for_each(|r| r.structural_hash(state));
         }
     }
-}
 
-#[cfg(feature = "actix-web")]
-pub use self::actix::*;
-
-#[cfg(feature = "axumweb")]
-mod axum {
-    use axum::{http::{header}, response::{Response, IntoResponse}, body};
-    use super::HalResource;
-
-
-    impl IntoResponse for HalResource {
-        fn into_response(self) -> Response {
-            if let Ok(res) = serde_json::to_string_pretty(&self) {
-                let body = body::boxed(body::Full::from(res));
-                Response::builder().status(200).header(header::CONTENT_TYPE, "application/hal+json; charset=urf-8").body(body).unwrap()
-            } else {
-                let body = body::boxed(body::Full::from(r#"{ "success": false, "error": "Invalid response" }"#.to_string()));
-                Response::builder().status(500).header(header::CONTENT_TYPE, "application/hal+json; charset=urf-8").body(body).unwrap()
-            }
+    /// Whether both resources are the same under `identity`.
+    pub fn identity_eq(&self, other: &HalResource, identity: &Identity) -> bool {
+        match identity {
+            Identity::SelfLink => match (self.self_href(), other.self_href()) {
+                (None, None) => self.structural_eq(other),
+                (a, b) => a == b,
+            },
+            Identity::PayloadKey(key) => match (self.payload_value(key), other.payload_value(key)) {
+                (None, None) => self.structural_eq(other),
+                (a, b) => a == b,
+            },
+            Identity::Full => self.structural_eq(other),
         }
     }
 
+    /// Hash the resource under `identity`, agreeing with `identity_eq`.
+    pub fn identity_hash<H: Hasher>(&self, identity: &Identity, state: &mut H) {
+        match identity {
+            Identity::SelfLink => match self.self_href() {
+                Some(href) => href.hash(state),
+                None => self.structural_hash(state),
+            },
+            Identity::PayloadKey(key) => match self.payload_value(key) {
+                Some(value) => identity::hash_json(value, state),
+                None => self.structural_hash(state),
+            },
+            Identity::Full => self.structural_hash(state),
+        }
+    }
 
-}
+    /// Borrow the resource to compare and hash it under `identity`.
+    pub fn identified<'a>(&'a self, identity: &'a Identity) -> Identified<'a> {
+        Identified::new(self, identity)
+    }
 
-#[cfg(feature = "warp-reply")]
-mod warp {
-
-    use super::HalResource;
-    use warp::{Reply };
-    use serde_json::to_string;
-    use http::header::{HeaderValue, CONTENT_TYPE};
-    use warp::http::{StatusCode };
-    use warp::reply::Response;
-
-    impl Reply for HalResource {
-        fn into_response(self) -> Response {
-            if let Ok(s) = to_string(&self) {
-                let mut response = Response::new(s.into());
-                response.headers_mut().insert(
-                    CONTENT_TYPE,
-                    HeaderValue::from_static("text/json; charset=utf-8")
-                );
-                response
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR.into_response()
-                // let mut response = Response::new("Error serializing resource".into());
-                // response.headers_mut().insert(
-                //     CONTENT_TYPE,
-                //     HeaderValue::from_static("text/plain; charset=utf-8"),
-                // );
-                // response.set_status(StatusCode::InternalServerError);
-                // response
-            }
+    fn payload_value(&self, key: &str) -> Option<&JsonValue> {
+        match self.data {
+            Some(JsonValue::Object(ref m)) => m.get(key),
+            _ => None,
         }
     }
+}
 
+/// Two resources are the same if their self links have the same href.
+/// Resources without a self link are compared structurally.
+impl PartialEq for HalResource {
+    fn eq(&self, other: &HalResource) -> bool {
+        self.identity_eq(other, &Identity::SelfLink)
+    }
 }
 
-#[cfg(feature = "warp-reply")]
-pub use self::warp::*;
+impl Eq for HalResource {}
 
+impl Hash for HalResource {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity_hash(&Identity::SelfLink, state)
+    }
+}
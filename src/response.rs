@@ -0,0 +1,670 @@
+//! HTTP responses carrying a HAL resource.
+//!
+//! `HalResponse` wraps a `HalResource` with the status code and headers to
+//! send it with. With the `actix`, `axumweb`, `warp-reply`, `rocket` or
+//! `poem` features it can be returned directly from a handler, and so can a
+//! bare `HalResource`, which is sent as `200 OK`.
+//!
+//! With `rocket` and `poem`, `HalResource` is also a request-body guard
+//! (Rocket) or extractor (Poem). Bodies declared as anything else than
+//! `application/hal+json` or `application/json` are rejected with a
+//! `415 Unsupported Media Type` problem, and malformed ones with a
+//! `400 Bad Request` problem. Rocket only sends those problems with the
+//! catchers of `problem_catchers`, and reads bodies up to the `json` limit.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::{HalResource, HalResponse};
+//!
+//! let res = HalResource::new(()).with_link("self", "/orders/12");
+//! let response = HalResponse::new(res).with_status(201).with_link_header(true);
+//!
+//! assert_eq!(response.status(), 201);
+//! assert_eq!(
+//!     response.headers(),
+//!     vec![("Link".to_string(), r#"</orders/12>; rel="self""#.to_string())]
+//! );
+//! ```
+use serde_json::to_string;
+
+use super::canonical;
+use super::deprecation::Deprecation;
+use super::forwarded::RequestOrigin;
+use super::link_policy::LinkPolicy;
+use super::problem::{Problem, VndError, PROBLEM_CONTENT_TYPE};
+use super::resource::HalResource;
+use super::{HalError, HalResult};
+
+/// The media type of HAL documents.
+pub const HAL_CONTENT_TYPE: &str = "application/hal+json; charset=utf-8";
+
+/// A HAL resource together with the status and headers to send it with.
+#[derive(Clone)]
+pub struct HalResponse {
+    resource: HalResource,
+    status: u16,
+    headers: Vec<(String, String)>,
+    link_header: bool,
+    content_type: &'static str,
+    etag: bool,
+    if_none_match: Option<String>,
+    deprecation: Option<Deprecation>,
+}
+
+/// A response with its body serialized, ready to hand over to a framework.
+#[derive(Clone, Debug)]
+pub struct Rendered {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HalResponse {
+    pub fn new(resource: HalResource) -> HalResponse {
+        HalResponse {
+            resource,
+            status: 200,
+            headers: Vec::new(),
+            link_header: false,
+            content_type: HAL_CONTENT_TYPE,
+            etag: true,
+            if_none_match: None,
+            deprecation: None,
+        }
+    }
+
+    /// Send a weak `ETag` computed from the canonical form of the resource
+    /// with `200 OK` responses; on by default.
+    pub fn with_etag(mut self, enabled: bool) -> Self {
+        self.etag = enabled;
+        self
+    }
+
+    /// The request's `If-None-Match` header: when it matches the ETag, the
+    /// response becomes `304 Not Modified`.
+    pub fn with_if_none_match(mut self, if_none_match: Option<String>) -> Self {
+        self.if_none_match = if_none_match;
+        self
+    }
+
+    /// Set the media type, `application/hal+json` by default.
+    pub fn with_content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        self.content_type
+    }
+
+    /// Set the status code, `200` by default.
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header.
+    pub fn with_header<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Also send the resource's links in an RFC 8288 `Link` header.
+    pub fn with_link_header(mut self, enabled: bool) -> Self {
+        self.link_header = enabled;
+        self
+    }
+
+    /// Filter the resource's links with `policy` for the caller described by
+    /// `ctx`, before anything is rendered.
+    pub fn with_link_policy<C, P>(mut self, policy: &P, ctx: &C) -> Self
+    where
+        C: ?Sized,
+        P: LinkPolicy<C> + ?Sized,
+    {
+        self.resource.apply_link_policy_mut(policy, ctx);
+        self
+    }
+
+    /// Make the resource's absolute-path hrefs absolute against the origin
+    /// the client used to reach the service.
+    pub fn with_origin(mut self, origin: &RequestOrigin) -> Self {
+        self.resource = origin.absolutize(self.resource);
+        self
+    }
+
+    /// Announce that the resource is served through a deprecated route,
+    /// with the `Deprecation`, `Sunset` and `Link` headers.
+    pub fn with_deprecation(mut self, deprecation: Deprecation) -> Self {
+        self.deprecation = Some(deprecation);
+        self
+    }
+
+    pub fn resource(&self) -> &HalResource {
+        &self.resource
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// The headers to send, besides `Content-Type`. Headers that are not
+    /// valid HTTP, such as a custom one with a line break, are logged and
+    /// left out, so that every framework sends the response without them.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        let mut headers = self.headers.clone();
+        if self.link_header {
+            let links = self.resource.to_link_header();
+            if !links.is_empty() {
+                headers.push(("Link".to_string(), links));
+            }
+        }
+        if let Some(ref deprecation) = self.deprecation {
+            headers.extend(deprecation.headers());
+        }
+        headers.retain(|(name, value)| {
+            let valid = is_valid_header(name, value);
+            if !valid {
+                log::warn!("Leaving out invalid header {}: {:?}", name, value);
+            }
+            valid
+        });
+        headers
+    }
+
+    /// The serialized resource.
+    pub fn body(&self) -> HalResult<String> {
+        Ok(to_string(&self.resource)?)
+    }
+
+    /// Serialize the response, turning a serialization failure into a
+    /// problem document.
+    pub fn render(&self) -> Rendered {
+        match self.body() {
+            Ok(body) => {
+                let mut rendered = Rendered {
+                    status: self.status,
+                    content_type: self.content_type,
+                    headers: self.headers(),
+                    body,
+                };
+                let has_etag = rendered
+                    .headers
+                    .iter()
+                    .any(|(name, _)| name.eq_ignore_ascii_case("etag"));
+                if self.etag && self.status == 200 && !has_etag {
+                    if let Ok(etag) = self.resource.etag() {
+                        rendered.headers.push(("ETag".to_string(), etag));
+                    }
+                }
+                let etag = rendered
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("etag"))
+                    .map(|(_, value)| value.clone());
+                if let (Some(etag), Some(header), 200) =
+                    (etag, self.if_none_match.as_ref(), self.status)
+                {
+                    if canonical::if_none_match(header, &etag) {
+                        rendered.status = 304;
+                        rendered.body = String::new();
+                    }
+                }
+                rendered
+            }
+            Err(e) => {
+                let problem = Problem::from(e);
+                Rendered {
+                    status: problem.status(),
+                    content_type: PROBLEM_CONTENT_TYPE,
+                    headers: Vec::new(),
+                    body: to_string(&problem.to_resource()).unwrap_or_default(),
+                }
+            }
+        }
+    }
+}
+
+/// Whether `name` is a token and `value` holds only visible ASCII, spaces
+/// and tabs, which every framework accepts.
+fn is_valid_header(name: &str, value: &str) -> bool {
+    let token = |b: u8| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b);
+    !name.is_empty()
+        && name.bytes().all(token)
+        && value.bytes().all(|b| b == b'\t' || b == b' ' || b.is_ascii_graphic())
+}
+
+/// The request's `If-None-Match` header, to pass to
+/// `HalResponse::with_if_none_match`.
+#[cfg(any(feature = "axumweb", feature = "poem"))]
+#[derive(Clone, Debug, Default)]
+pub struct IfNoneMatch(pub Option<String>);
+
+/// Parse a request body into a resource, checking its content type.
+#[cfg(any(feature = "rocket", feature = "poem"))]
+#[allow(clippy::result_large_err)]
+fn read_body(content_type: Option<&str>, body: &[u8]) -> Result<HalResource, Problem> {
+    if let Some(content_type) = content_type {
+        let media = content_type.split(';').next().unwrap_or("").trim();
+        let hal = HAL_CONTENT_TYPE.split(';').next().unwrap_or("");
+        if !media.eq_ignore_ascii_case(hal) && !media.eq_ignore_ascii_case("application/json") {
+            return Err(Problem::new(415)
+                .with_title("Unsupported media type")
+                .with_detail(&format!("Expected {}, got {}", hal, media)));
+        }
+    }
+    serde_json::from_slice(body).map_err(|e| {
+        Problem::new(400)
+            .with_title("Malformed HAL document")
+            .with_detail(&e.to_string())
+    })
+}
+
+impl From<HalError> for HalResponse {
+    fn from(error: HalError) -> Self {
+        Problem::from(error).to_response()
+    }
+}
+
+impl From<Problem> for HalResponse {
+    fn from(problem: Problem) -> Self {
+        problem.to_response()
+    }
+}
+
+impl From<VndError> for HalResponse {
+    fn from(error: VndError) -> Self {
+        error.to_response()
+    }
+}
+
+impl From<HalResource> for HalResponse {
+    fn from(resource: HalResource) -> Self {
+        HalResponse::new(resource)
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+
+    use super::HalResponse;
+    use crate::problem::{Problem, VndError};
+    use crate::resource::HalResource;
+    use crate::HalError;
+    use actix_web::body::BoxBody;
+    use actix_web::http::header::IF_NONE_MATCH;
+    use actix_web::http::StatusCode;
+    use actix_web::{HttpRequest, HttpResponse, Responder, ResponseError};
+
+    impl From<HalResponse> for HttpResponse {
+        fn from(response: HalResponse) -> Self {
+            let rendered = response.render();
+            let status =
+                StatusCode::from_u16(rendered.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            let mut builder = HttpResponse::build(status);
+            builder.content_type(rendered.content_type);
+            for header in rendered.headers {
+                builder.append_header(header);
+            }
+            builder.body(rendered.body)
+        }
+    }
+
+    macro_rules! actix_responder {
+        ($t:ty) => {
+            impl Responder for $t {
+                type Body = BoxBody;
+
+                fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+                    HttpResponse::from(HalResponse::from(self))
+                }
+            }
+        };
+    }
+
+    impl Responder for HalResponse {
+        type Body = BoxBody;
+
+        fn respond_to(mut self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+            if self.if_none_match.is_none() {
+                self.if_none_match = req
+                    .headers()
+                    .get(IF_NONE_MATCH)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from);
+            }
+            self.into()
+        }
+    }
+
+    actix_responder!(Problem);
+    actix_responder!(VndError);
+
+    impl From<HalResource> for HttpResponse {
+        fn from(resource: HalResource) -> Self {
+            HalResponse::new(resource).into()
+        }
+    }
+
+    impl Responder for HalResource {
+        type Body = BoxBody;
+
+        fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+            HalResponse::new(self).respond_to(req)
+        }
+    }
+
+    impl ResponseError for HalError {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+
+        fn error_response(&self) -> HttpResponse {
+            Problem::from(self).to_response().into()
+        }
+    }
+}
+
+#[cfg(feature = "axumweb")]
+mod axum {
+    use super::{HalResponse, IfNoneMatch};
+    use crate::problem::{Problem, VndError};
+    use crate::resource::HalResource;
+    use crate::HalError;
+    use axum::body::Body;
+    use axum::extract::FromRequestParts;
+    use axum::http::request::Parts;
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use std::convert::Infallible;
+
+    impl<S: Send + Sync> FromRequestParts<S> for IfNoneMatch {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+            Ok(IfNoneMatch(
+                parts
+                    .headers
+                    .get(header::IF_NONE_MATCH)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from),
+            ))
+        }
+    }
+
+    impl IntoResponse for HalResponse {
+        fn into_response(self) -> Response {
+            let rendered = self.render();
+            let mut builder = Response::builder()
+                .status(rendered.status)
+                .header(header::CONTENT_TYPE, rendered.content_type);
+            for (name, value) in rendered.headers {
+                builder = builder.header(name, value);
+            }
+            builder
+                .body(Body::from(rendered.body))
+                .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+
+    macro_rules! axum_responder {
+        ($t:ty) => {
+            impl IntoResponse for $t {
+                fn into_response(self) -> Response {
+                    HalResponse::from(self).into_response()
+                }
+            }
+        };
+    }
+
+    axum_responder!(HalResource);
+    axum_responder!(Problem);
+    axum_responder!(VndError);
+    axum_responder!(HalError);
+}
+
+#[cfg(feature = "warp-reply")]
+mod warp {
+
+    use super::HalResponse;
+    use crate::problem::{Problem, VndError};
+    use crate::resource::HalResource;
+    use crate::HalError;
+    use http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+    use warp::http::StatusCode;
+    use warp::reply::Response;
+    use std::convert::Infallible;
+    use warp::{Filter, Reply};
+
+    /// A filter extracting the request's `If-None-Match` header, to pass to
+    /// `HalResponse::with_if_none_match`.
+    pub fn if_none_match() -> impl Filter<Extract = (Option<String>,), Error = Infallible> + Clone
+    {
+        warp::header::optional::<String>("if-none-match")
+            .or(warp::any().map(|| None))
+            .unify()
+    }
+
+    impl Reply for HalResponse {
+        fn into_response(self) -> Response {
+            let rendered = self.render();
+            let mut response = Response::new(rendered.body.into());
+            *response.status_mut() =
+                StatusCode::from_u16(rendered.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            let headers = response.headers_mut();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(rendered.content_type));
+            for (name, value) in rendered.headers {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.as_bytes()),
+                    HeaderValue::from_str(&value),
+                ) {
+                    headers.append(name, value);
+                }
+            }
+            response
+        }
+    }
+
+    macro_rules! warp_reply {
+        ($t:ty) => {
+            impl Reply for $t {
+                fn into_response(self) -> Response {
+                    HalResponse::from(self).into_response()
+                }
+            }
+        };
+    }
+
+    warp_reply!(HalResource);
+    warp_reply!(Problem);
+    warp_reply!(VndError);
+    warp_reply!(HalError);
+}
+
+#[cfg(feature = "warp-reply")]
+pub use self::warp::if_none_match;
+
+#[cfg(feature = "rocket")]
+mod rocket {
+    use super::{read_body, HalResponse};
+    use crate::problem::{Problem, VndError};
+    use crate::resource::HalResource;
+    use crate::HalError;
+    use rocket::data::{Data, FromData, Limits, Outcome};
+    use rocket::http::Status;
+    use rocket::response::{self, Responder, Response};
+    use rocket::{Catcher, Request};
+    use std::io::Cursor;
+
+    /// The problem a request-body guard failed with, for the catchers.
+    struct BodyProblem(Option<Problem>);
+
+    impl<'r> Responder<'r, 'static> for HalResponse {
+        fn respond_to(mut self, req: &'r Request<'_>) -> response::Result<'static> {
+            if self.if_none_match.is_none() {
+                self.if_none_match = req.headers().get_one("If-None-Match").map(String::from);
+            }
+            let rendered = self.render();
+            let mut builder = Response::build();
+            builder
+                .status(Status::new(rendered.status))
+                .raw_header("Content-Type", rendered.content_type);
+            for (name, value) in rendered.headers {
+                builder.raw_header_adjoin(name, value);
+            }
+            Ok(builder
+                .sized_body(rendered.body.len(), Cursor::new(rendered.body))
+                .finalize())
+        }
+    }
+
+    macro_rules! rocket_responder {
+        ($t:ty) => {
+            impl<'r> Responder<'r, 'static> for $t {
+                fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+                    HalResponse::from(self).respond_to(req)
+                }
+            }
+        };
+    }
+
+    rocket_responder!(HalResource);
+    rocket_responder!(Problem);
+    rocket_responder!(VndError);
+    rocket_responder!(HalError);
+
+    #[rocket::async_trait]
+    impl<'r> FromData<'r> for HalResource {
+        type Error = Problem;
+
+        async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+            let limit = req.limits().get("json").unwrap_or(Limits::JSON);
+            let fail = |problem: Problem| {
+                req.local_cache(|| BodyProblem(Some(problem.clone())));
+                Outcome::Error((Status::new(problem.status()), problem))
+            };
+            let body = match data.open(limit).into_bytes().await {
+                Ok(body) if body.is_complete() => body.into_inner(),
+                Ok(_) => {
+                    return fail(
+                        Problem::new(413)
+                            .with_title("Payload too large")
+                            .with_detail(&format!("The body exceeds {}", limit)),
+                    )
+                }
+                Err(e) => {
+                    return fail(
+                        Problem::new(400)
+                            .with_title("Unreadable body")
+                            .with_detail(&e.to_string()),
+                    )
+                }
+            };
+            match read_body(req.headers().get_one("Content-Type"), &body) {
+                Ok(resource) => Outcome::Success(resource),
+                Err(problem) => fail(problem),
+            }
+        }
+    }
+
+    #[rocket::catch(default)]
+    fn problem(status: Status, req: &Request<'_>) -> Problem {
+        match req.local_cache(|| BodyProblem(None)) {
+            BodyProblem(Some(problem)) => problem.clone(),
+            BodyProblem(None) => {
+                let problem = Problem::new(status.code);
+                match status.reason() {
+                    Some(reason) => problem.with_title(reason),
+                    None => problem,
+                }
+            }
+        }
+    }
+
+    /// Catchers sending every error as a problem document, including the
+    /// ones of the `HalResource` body guard. Register them with
+    /// `rocket.register("/", problem_catchers())`.
+    pub fn problem_catchers() -> Vec<Catcher> {
+        rocket::catchers![problem]
+    }
+}
+
+#[cfg(feature = "rocket")]
+pub use self::rocket::problem_catchers;
+
+#[cfg(feature = "poem")]
+mod poem {
+    use super::{read_body, HalResponse, IfNoneMatch};
+    use crate::problem::{Problem, VndError};
+    use crate::resource::HalResource;
+    use crate::HalError;
+    use poem::error::ResponseError;
+    use poem::http::StatusCode;
+    use poem::{Error, FromRequest, IntoResponse, Request, RequestBody, Response};
+
+    impl IntoResponse for HalResponse {
+        fn into_response(self) -> Response {
+            let rendered = self.render();
+            let status =
+                StatusCode::from_u16(rendered.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            let mut builder = Response::builder()
+                .status(status)
+                .content_type(rendered.content_type);
+            for (name, value) in rendered.headers {
+                builder = builder.header(name, value);
+            }
+            builder.body(rendered.body)
+        }
+    }
+
+    macro_rules! poem_responder {
+        ($t:ty) => {
+            impl IntoResponse for $t {
+                fn into_response(self) -> Response {
+                    HalResponse::from(self).into_response()
+                }
+            }
+        };
+    }
+
+    poem_responder!(HalResource);
+    poem_responder!(Problem);
+    poem_responder!(VndError);
+    poem_responder!(HalError);
+
+    impl ResponseError for HalError {
+        fn status(&self) -> StatusCode {
+            StatusCode::from_u16(HalError::status(self)).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+
+        fn as_response(&self) -> Response {
+            Problem::from(self).to_response().into_response()
+        }
+    }
+
+    impl<'a> FromRequest<'a> for IfNoneMatch {
+        async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+            Ok(IfNoneMatch(req.header("If-None-Match").map(String::from)))
+        }
+    }
+
+    impl<'a> FromRequest<'a> for HalResource {
+        async fn from_request(req: &'a Request, body: &mut RequestBody) -> poem::Result<Self> {
+            let problem = |problem: Problem| Error::from_response(problem.into_response());
+            let body = body.take()?.into_bytes().await.map_err(|e| {
+                problem(
+                    Problem::new(400)
+                        .with_title("Unreadable body")
+                        .with_detail(&e.to_string()),
+                )
+            })?;
+            read_body(req.content_type(), &body).map_err(problem)
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Links built from typed route definitions.
+//!
+//! A `HalRoute` ties a route's path, in axum syntax (`/orders/{id}`,
+//! `/files/{*path}`), to the type of its parameters. The same constant can
+//! be used to register the handler and to build links to it, so that a
+//! route change cannot silently break the hrefs a service emits.
+//!
+//! `link_to` fills the path parameters from any serializable value, usually
+//! a struct with the same fields as the route's `Path` extractor. Parameters
+//! left unbound (missing or `None`) stay in the href as URI template
+//! variables, and the link is marked templated.
+//!
+//! With the `axumweb` feature, the `RoutePrefix` extractor finds the prefix
+//! a nested router is mounted under, and prepends it to the links.
+//!
+//! With the `actix` feature, `HalLink::from_route` and
+//! `HalResource::with_route_link` build links to named actix resources
+//! through `HttpRequest::url_for`, falling back to a templated link when
+//! segments are missing.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::route::{link_to, HalRoute};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct OrderPath {
+//!     id: Option<u64>,
+//! }
+//!
+//! struct OrderRoute;
+//!
+//! impl HalRoute for OrderRoute {
+//!     const PATH: &'static str = "/orders/{id}";
+//!     type Params = OrderPath;
+//! }
+//!
+//! // Router::new().route(OrderRoute::PATH, get(order))
+//! let link = link_to::<OrderRoute>(OrderPath { id: Some(12) });
+//! assert_eq!(link.href, "/orders/12");
+//!
+//! let link = link_to::<OrderRoute>(OrderPath { id: None });
+//! assert_eq!(link.href, "/orders/{id}");
+//! assert!(link.templated);
+//! ```
+use serde::Serialize;
+use serde_json::{to_value, Value as JsonValue};
+
+use super::link::HalLink;
+use super::uri;
+
+/// A route with a path and a type for its parameters.
+pub trait HalRoute {
+    /// The path, in axum syntax.
+    const PATH: &'static str;
+    /// The parameters of the path, serializing to an object.
+    type Params: Serialize;
+}
+
+/// Fill the parameters of `path` from `params`. Unbound `{name}` parameters
+/// are left as they are, and unbound `{*name}` ones become `{+name}`, their
+/// URI template equivalent; see `uri::expand`.
+pub fn expand<P: Serialize>(path: &str, params: &P) -> HalLink {
+    let params = to_value(params).unwrap_or(JsonValue::Null);
+    let (href, templated) = uri::expand(path, &params);
+    HalLink::new(href).templated(templated)
+}
+
+/// A link to the route `R` with `params`.
+pub fn link_to<R: HalRoute>(params: R::Params) -> HalLink {
+    expand(R::PATH, &params)
+}
+
+/// The path prefix a router is mounted under, such as `/api` for a router
+/// nested with `Router::nest("/api", ...)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoutePrefix(pub String);
+
+impl RoutePrefix {
+    /// The prefix, given the path the client requested and the path the
+    /// router matched once the prefix was stripped.
+    pub fn from_paths(original: &str, matched: &str) -> RoutePrefix {
+        let prefix = match original.strip_suffix(matched) {
+            Some(prefix) => prefix,
+            None if matched == "/" => original,
+            None => "",
+        };
+        RoutePrefix(prefix.trim_end_matches('/').to_string())
+    }
+
+    /// A link to the route `R` with `params`, under this prefix.
+    pub fn link_to<R: HalRoute>(&self, params: R::Params) -> HalLink {
+        let mut link = link_to::<R>(params);
+        link.href = format!("{}{}", self.0, link.href);
+        link
+    }
+}
+
+#[cfg(feature = "axumweb")]
+mod axum {
+    use super::RoutePrefix;
+    use axum::extract::{FromRequestParts, OriginalUri};
+    use axum::http::request::Parts;
+    use std::convert::Infallible;
+
+    impl<S: Send + Sync> FromRequestParts<S> for RoutePrefix {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+            let matched = parts.uri.path().to_string();
+            Ok(match parts.extensions.get::<OriginalUri>() {
+                Some(OriginalUri(original)) => RoutePrefix::from_paths(original.path(), &matched),
+                None => RoutePrefix::default(),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+pub(crate) mod actix {
+    use super::super::link::HalLink;
+    use super::super::resource::HalResource;
+    use super::super::uri;
+    use super::super::{HalError, HalResult};
+    use actix_web::error::UrlGenerationError;
+    use actix_web::HttpRequest;
+
+    /// The most segments tried when filling in missing ones.
+    const MAX_SEGMENTS: usize = 16;
+
+    /// A stand-in for the dynamic segment at `index`, made of letters so
+    /// that `url_for` leaves it as is and `[a-z]+` segments match it: the
+    /// index in base 26 (`a`, `b`, … `ba`), padded with `a` to the width of
+    /// the largest of `count` placeholders so that none is a prefix of
+    /// another.
+    pub(crate) fn placeholder(index: usize, count: usize) -> String {
+        let mut width = 1;
+        let mut largest = count.saturating_sub(1) / 26;
+        while largest > 0 {
+            width += 1;
+            largest /= 26;
+        }
+        let mut letters = vec!['a'; width];
+        let mut rest = index;
+        for letter in letters.iter_mut().rev() {
+            *letter = (b'a' + (rest % 26) as u8) as char;
+            rest /= 26;
+        }
+        let mut out = String::from("halsegment");
+        out.extend(letters);
+        out
+    }
+
+    /// An actix pattern in axum syntax: `{name:regex}` segments become
+    /// `{name}`, and a `{name}*` tail `{*name}`.
+    fn axum_path(pattern: &str) -> String {
+        let mut out = String::with_capacity(pattern.len());
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '{' {
+                out.push(c);
+                continue;
+            }
+            let mut depth = 1;
+            let mut segment = String::new();
+            for c in chars.by_ref() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    break;
+                }
+                segment.push(c);
+            }
+            let name = segment.split(':').next().unwrap_or("");
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                out.push_str(&format!("{{*{}}}", name));
+            } else {
+                out.push_str(&format!("{{{}}}", name));
+            }
+        }
+        out
+    }
+
+    /// The pattern of the resource named `name`, if `path` matches it and
+    /// no other resource.
+    fn pattern(req: &HttpRequest, name: &str, path: &str) -> Option<String> {
+        let map = req.resource_map();
+        if map.match_name(path) != Some(name) {
+            return None;
+        }
+        map.match_pattern(path)
+    }
+
+    impl HalLink {
+        /// A link to the actix resource named `name`, with `elements` for
+        /// its dynamic segments. Missing segments are left as URI template
+        /// variables, and the link is then templated.
+        ///
+        /// Actix does not give the pattern of a named resource, so it is
+        /// found by matching a path with stand-ins for the missing segments.
+        /// When the segments' regexes reject both letters and digits, the
+        /// variables are named after their position, as in `{segment2}`.
+        pub fn from_route<I: AsRef<str>>(
+            req: &HttpRequest,
+            name: &str,
+            elements: &[I],
+        ) -> HalResult<HalLink> {
+            let error =
+                |e: UrlGenerationError| HalError::Custom(format!("No route to {}: {}", name, e));
+            match req.url_for(name, elements) {
+                Ok(url) => return Ok(HalLink::new(url.as_str())),
+                Err(UrlGenerationError::NotEnoughElements) => {}
+                Err(e) => return Err(error(e)),
+            }
+            let given: Vec<&str> = elements.iter().map(AsRef::as_ref).collect();
+            for missing in 1..=MAX_SEGMENTS {
+                let count = given.len() + missing;
+                let placeholders: Vec<String> =
+                    (given.len()..count).map(|index| placeholder(index, count)).collect();
+                let padded = given.iter().copied().chain(placeholders.iter().map(String::as_str));
+                let url = match req.url_for(name, padded) {
+                    Ok(url) => url,
+                    Err(UrlGenerationError::NotEnoughElements) => continue,
+                    Err(e) => return Err(error(e)),
+                };
+                let found = pattern(req, name, url.path()).or_else(|| {
+                    // repeat_n needs Rust 1.82.
+                    #[allow(clippy::manual_repeat_n)]
+                    let zeros = given.iter().copied().chain(std::iter::repeat("0").take(missing));
+                    pattern(req, name, req.url_for(name, zeros).ok()?.path())
+                });
+                let path = match found {
+                    Some(pattern) => {
+                        let mut values = given.iter();
+                        let lookup = |_: &str| values.next().map(|v| v.to_string());
+                        uri::expand_with(&axum_path(&pattern), lookup).0
+                    }
+                    None => placeholders.iter().enumerate().fold(
+                        url.path().to_string(),
+                        |path, (i, placeholder)| {
+                            path.replace(placeholder, &format!("{{segment{}}}", given.len() + i))
+                        },
+                    ),
+                };
+                let origin = url.origin().ascii_serialization();
+                return Ok(HalLink::new(format!("{}{}", origin, path)).templated(true));
+            }
+            Err(error(UrlGenerationError::NotEnoughElements))
+        }
+    }
+
+    impl HalResource {
+        /// Add a link under `rel` to the actix resource named `name`; see
+        /// `HalLink::from_route`.
+        pub fn with_route_link<I: AsRef<str>>(
+            self,
+            rel: &str,
+            req: &HttpRequest,
+            name: &str,
+            elements: &[I],
+        ) -> HalResult<Self> {
+            Ok(self.with_link(rel, HalLink::from_route(req, name, elements)?))
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Server-Sent Events streams of HAL resources.
+//!
+//! Each resource of a stream is sent as one `text/event-stream` event whose
+//! data is the HAL document. The event type is the resource's `state`
+//! payload field, and the event id is the href of its self link or, without
+//! one, its ETag, so that a reconnecting client can send it back in
+//! `Last-Event-ID`.
+//!
+//! With the `axumweb` and `actix` features, `HalSse` wraps a
+//! `Stream<Item = HalResource>` and can be returned from a handler. On the
+//! client side, `HalEventReader` decodes the bytes of an event stream, as
+//! they arrive, into `HalEvent`s holding typed resources.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::sse::{HalEvent, HalEventReader};
+//! use rustic_hal::HalResource;
+//! use serde_json::json;
+//!
+//! let order = HalResource::new(json!({"id": 12, "state": "paid"})).with_link("self", "/orders/12");
+//! let event = HalEvent::from_resource(&order);
+//! assert_eq!(
+//!     event.encode(),
+//!     "event: paid\nid: /orders/12\ndata: {\"_links\":{\"self\":{\"href\":\"/orders/12\"}},\"id\":12,\"state\":\"paid\"}\n\n"
+//! );
+//!
+//! let mut reader = HalEventReader::new();
+//! let events = reader.feed(event.encode().as_bytes());
+//! assert_eq!(events[0].event.as_deref(), Some("paid"));
+//! assert_eq!(events[0].get_data::<serde_json::Value>().unwrap()["id"], 12);
+//! ```
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{from_str, to_string};
+
+use super::problem::Problem;
+use super::resource::HalResource;
+use super::HalResult;
+
+/// The media type of event streams.
+pub const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+
+/// The payload field holding the state of a resource.
+pub const STATE_KEY: &str = "state";
+
+/// Whether `value` can be sent in an `event` or `id` field.
+fn is_field_safe(value: &str) -> bool {
+    !value.contains(['\n', '\r', '\0'])
+}
+
+/// One event of a stream.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HalEvent {
+    /// The event type; `message` when unset.
+    pub event: Option<String>,
+    pub id: Option<String>,
+    /// The serialized HAL document.
+    pub data: String,
+}
+
+impl HalEvent {
+    /// The event for `resource`. A resource that cannot be serialized gives
+    /// an `error` event holding a problem document instead.
+    pub fn from_resource(resource: &HalResource) -> HalEvent {
+        let data = match to_string(resource) {
+            Ok(data) => data,
+            Err(e) => {
+                let problem = Problem::from(super::HalError::from(e)).to_resource();
+                return HalEvent {
+                    event: Some("error".to_string()),
+                    id: None,
+                    data: to_string(&problem).unwrap_or_default(),
+                };
+            }
+        };
+        let event = resource
+            .get_extra_data::<String>(STATE_KEY)
+            .ok()
+            .filter(|state| is_field_safe(state));
+        let id = match resource.self_href() {
+            Some(href) => Some(href.to_string()),
+            None => resource.etag().ok(),
+        };
+        HalEvent {
+            event,
+            id: id.filter(|id| is_field_safe(id)),
+            data,
+        }
+    }
+
+    /// Override the event type; ignored if it holds a line break.
+    pub fn with_event(mut self, event: &str) -> Self {
+        if is_field_safe(event) {
+            self.event = Some(event.to_string());
+        }
+        self
+    }
+
+    /// Override the event id; ignored if it holds a line break.
+    pub fn with_id(mut self, id: &str) -> Self {
+        if is_field_safe(id) {
+            self.id = Some(id.to_string());
+        }
+        self
+    }
+
+    /// The event in the `text/event-stream` format, blank line included.
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        if let Some(ref event) = self.event {
+            out.push_str(&format!("event: {}\n", event));
+        }
+        if let Some(ref id) = self.id {
+            out.push_str(&format!("id: {}\n", id));
+        }
+        for line in self.data.split('\n') {
+            out.push_str(&format!("data: {}\n", line.trim_end_matches('\r')));
+        }
+        out.push('\n');
+        out
+    }
+
+    /// The HAL document of the event.
+    pub fn resource(&self) -> HalResult<HalResource> {
+        Ok(from_str(&self.data)?)
+    }
+
+    /// The payload of the event's resource, as a `V`.
+    pub fn get_data<V>(&self) -> HalResult<V>
+    where
+        for<'de> V: Deserialize<'de>,
+    {
+        self.resource()?.get_data()
+    }
+}
+
+/// An incremental decoder of `text/event-stream` bodies, following the
+/// [HTML standard](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+#[derive(Clone, Debug, Default)]
+pub struct HalEventReader {
+    buffer: Vec<u8>,
+    started: bool,
+    skip_lf: bool,
+    event: Option<String>,
+    data: Option<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl HalEventReader {
+    pub fn new() -> HalEventReader {
+        HalEventReader::default()
+    }
+
+    /// The id of the last event, to send back in `Last-Event-ID` when
+    /// reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// The reconnection delay the server asked for.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Decode the next `chunk` of the stream, giving back the events it
+    /// completes. Chunks may split lines, and even characters, anywhere.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<HalEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if self.skip_lf {
+                self.skip_lf = false;
+                if byte == b'\n' {
+                    continue;
+                }
+            }
+            match byte {
+                b'\r' | b'\n' => {
+                    self.skip_lf = byte == b'\r';
+                    let line = std::mem::take(&mut self.buffer);
+                    if let Some(event) = self.line(&String::from_utf8_lossy(&line)) {
+                        events.push(event);
+                    }
+                }
+                byte => self.buffer.push(byte),
+            }
+        }
+        events
+    }
+
+    fn line(&mut self, line: &str) -> Option<HalEvent> {
+        let line = if self.started {
+            line
+        } else {
+            self.started = true;
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        };
+        if line.is_empty() {
+            let event = self.event.take();
+            let data = self.data.take()?;
+            return Some(HalEvent {
+                event,
+                id: self.last_event_id.clone(),
+                data,
+            });
+        }
+        let (field, value) = match line.find(':') {
+            Some(0) => return None,
+            Some(i) => {
+                let value = &line[i + 1..];
+                (&line[..i], value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match self.data {
+                Some(ref mut data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(ms) = value.parse::<u64>() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// A stream of resources sent as Server-Sent Events.
+pub struct HalSse<S>(pub S);
+
+impl<S> HalSse<S> {
+    pub fn new(stream: S) -> HalSse<S> {
+        HalSse(stream)
+    }
+}
+
+#[cfg(feature = "axumweb")]
+mod axum {
+    use super::{HalEvent, HalSse};
+    use crate::resource::HalResource;
+    use axum::response::sse::{Event, Sse};
+    use axum::response::{IntoResponse, Response};
+    use futures_core::Stream;
+    use futures_util::StreamExt;
+    use std::convert::Infallible;
+
+    impl From<HalEvent> for Event {
+        fn from(event: HalEvent) -> Self {
+            let mut out = Event::default().data(event.data);
+            if let Some(ref name) = event.event {
+                out = out.event(name);
+            }
+            if let Some(ref id) = event.id {
+                out = out.id(id);
+            }
+            out
+        }
+    }
+
+    impl<S> IntoResponse for HalSse<S>
+    where
+        S: Stream<Item = HalResource> + Send + 'static,
+    {
+        fn into_response(self) -> Response {
+            let events = self
+                .0
+                .map(|resource| Ok::<_, Infallible>(HalEvent::from_resource(&resource).into()));
+            Sse::new(events).into_response()
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{HalEvent, HalSse, EVENT_STREAM_CONTENT_TYPE};
+    use crate::resource::HalResource;
+    use actix_web::body::BoxBody;
+    use actix_web::http::header::CACHE_CONTROL;
+    use actix_web::web::Bytes;
+    use actix_web::{HttpRequest, HttpResponse, Responder};
+    use futures::{Stream, StreamExt};
+    use std::convert::Infallible;
+
+    impl<S> Responder for HalSse<S>
+    where
+        S: Stream<Item = HalResource> + 'static,
+    {
+        type Body = BoxBody;
+
+        fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+            let events = self.0.map(|resource| {
+                Ok::<_, Infallible>(Bytes::from(HalEvent::from_resource(&resource).encode()))
+            });
+            HttpResponse::Ok()
+                .content_type(EVENT_STREAM_CONTENT_TYPE)
+                .insert_header((CACHE_CONTROL, "no-cache"))
+                .streaming(events)
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Declarative hypermedia state machines.
+//!
+//! A `HalStateMachine` declares the states of a kind of resource and the
+//! transitions between them, each one exposed as a link: a relation, an HTTP
+//! method and an href template. Given the current state of a resource, the
+//! machine attaches exactly the links of the transitions allowed from it,
+//! with the method in the `method` link extension. The variables of the
+//! href templates are filled from the resource's payload, as `uri::expand`
+//! does.
+//!
+//! The machine can be exported as a Mermaid or Graphviz diagram for the
+//! documentation, with the names escaped as each syntax requires.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::state_machine::HalStateMachine;
+//! use rustic_hal::HalResource;
+//! use serde_json::json;
+//!
+//! let orders = HalStateMachine::new("Order")
+//!     .with_transition("pending", "paid", "pay", "POST", "/orders/{id}/payment")
+//!     .with_transition("pending", "cancelled", "cancel", "DELETE", "/orders/{id}")
+//!     .with_transition("paid", "shipped", "ship", "POST", "/orders/{id}/shipment");
+//!
+//! let order = orders.apply(HalResource::new(json!({"id": 12})), "pending");
+//! let pay = order.get_link("pay").unwrap();
+//! assert_eq!(pay.href, "/orders/12/payment");
+//! assert_eq!(pay.extension::<String>("method"), Some("POST".to_string()));
+//! assert!(order.get_link("ship").is_none());
+//!
+//! assert_eq!(orders.next_state("paid", "ship").unwrap(), "shipped");
+//! assert!(orders.to_mermaid().contains("pending --> paid : pay"));
+//! ```
+use serde_json::Value as JsonValue;
+
+use super::link::HalLink;
+use super::resource::HalResource;
+use super::uri;
+use super::{HalError, HalResult};
+
+/// A transition between two states, exposed as a link.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub rel: String,
+    pub method: String,
+    /// The href, possibly a URI template with `{name}` variables.
+    pub href: String,
+    pub title: Option<String>,
+}
+
+impl Transition {
+    /// The link for this transition, with the template variables found in
+    /// `payload` filled in.
+    pub fn link(&self, payload: Option<&JsonValue>) -> HalLink {
+        let (href, templated) = uri::expand(&self.href, payload.unwrap_or(&JsonValue::Null));
+        let link = HalLink::new(href)
+            .templated(templated)
+            .with_extension("method", &self.method);
+        match self.title {
+            Some(ref title) => link.with_title(title),
+            None => link,
+        }
+    }
+}
+
+/// Whether `state` can be written as is in a Mermaid diagram. The others
+/// get an id starting with `_`, which plain names cannot.
+fn is_plain(state: &str) -> bool {
+    state.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && state.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `text` with the characters Mermaid gives a meaning to written as entity
+/// codes.
+fn mermaid_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '#' | ';' | ':' | '[' | ']' | '{' | '}' | '<' | '>' => {
+                out.push_str(&format!("#{};", c as u32))
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `text` as a Graphviz quoted string.
+fn dot_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The states of a kind of resource and the transitions between them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HalStateMachine {
+    name: String,
+    states: Vec<String>,
+    transitions: Vec<Transition>,
+}
+
+impl HalStateMachine {
+    pub fn new(name: &str) -> HalStateMachine {
+        HalStateMachine {
+            name: name.to_string(),
+            ..HalStateMachine::default()
+        }
+    }
+
+    /// Declare a state, such as a final one with no transition.
+    pub fn with_state(mut self, state: &str) -> Self {
+        if !self.states.iter().any(|s| s == state) {
+            self.states.push(state.to_string());
+        }
+        self
+    }
+
+    /// Allow going from `from` to `to` by calling `method` on `href`,
+    /// exposed as a link under `rel`.
+    pub fn with_transition(
+        self,
+        from: &str,
+        to: &str,
+        rel: &str,
+        method: &str,
+        href: &str,
+    ) -> Self {
+        self.with_transition_titled(from, to, rel, method, href, None)
+    }
+
+    /// Same as `with_transition`, with a title for the link.
+    pub fn with_transition_titled(
+        mut self,
+        from: &str,
+        to: &str,
+        rel: &str,
+        method: &str,
+        href: &str,
+        title: Option<&str>,
+    ) -> Self {
+        self = self.with_state(from).with_state(to);
+        self.transitions.push(Transition {
+            from: from.to_string(),
+            to: to.to_string(),
+            rel: rel.to_string(),
+            method: method.to_ascii_uppercase(),
+            href: href.to_string(),
+            title: title.map(str::to_string),
+        });
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The declared states, in order of declaration.
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    /// The transitions allowed from `state`.
+    pub fn transitions_from<'a>(&'a self, state: &'a str) -> impl Iterator<Item = &'a Transition> {
+        self.transitions.iter().filter(move |t| t.from == state)
+    }
+
+    /// The state reached by following `rel` from `state`.
+    pub fn next_state(&self, state: &str, rel: &str) -> HalResult<&str> {
+        self.transitions
+            .iter()
+            .find(|t| t.from == state && t.rel == rel)
+            .map(|t| t.to.as_str())
+            .ok_or_else(|| {
+                HalError::Custom(format!(
+                    "No transition '{}' from state '{}' of {}",
+                    rel, state, self.name
+                ))
+            })
+    }
+
+    /// Attach to `resource` the links of the transitions allowed from `state`.
+    pub fn apply(&self, resource: HalResource, state: &str) -> HalResource {
+        let links: Vec<_> = {
+            let payload = resource.get_data::<JsonValue>().ok();
+            self.transitions_from(state)
+                .map(|t| (t.rel.clone(), t.link(payload.as_ref())))
+                .collect()
+        };
+        links
+            .into_iter()
+            .fold(resource, |resource, (rel, link)| resource.with_link(rel, link))
+    }
+
+    /// The id of `state` in a Mermaid diagram.
+    fn mermaid_id(&self, state: &str) -> String {
+        if is_plain(state) {
+            return state.to_string();
+        }
+        let index = self.states.iter().position(|s| s == state).unwrap_or(0);
+        format!("_{}", index)
+    }
+
+    /// A Mermaid `stateDiagram-v2` of the machine. States whose name is not
+    /// a plain identifier are declared with an id and the name as label.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("stateDiagram-v2\n");
+        for state in self.states.iter() {
+            if is_plain(state) {
+                out.push_str(&format!("    {}\n", state));
+            } else {
+                out.push_str(&format!(
+                    "    state \"{}\" as {}\n",
+                    mermaid_text(state),
+                    self.mermaid_id(state)
+                ));
+            }
+        }
+        for t in self.transitions.iter() {
+            out.push_str(&format!(
+                "    {} --> {} : {} ({})\n",
+                self.mermaid_id(&t.from),
+                self.mermaid_id(&t.to),
+                mermaid_text(&t.rel),
+                mermaid_text(&t.method)
+            ));
+        }
+        out
+    }
+
+    /// A Graphviz `digraph` of the machine.
+    pub fn to_dot(&self) -> String {
+        let mut out = format!("digraph {} {{\n", dot_string(&self.name));
+        for state in self.states.iter() {
+            out.push_str(&format!("    {};\n", dot_string(state)));
+        }
+        for t in self.transitions.iter() {
+            out.push_str(&format!(
+                "    {} -> {} [label={}];\n",
+                dot_string(&t.from),
+                dot_string(&t.to),
+                dot_string(&format!("{} ({} {})", t.rel, t.method, t.href))
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
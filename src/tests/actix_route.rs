@@ -0,0 +1,51 @@
+use super::super::{HalLink, HalResource};
+use actix_web::{test, web, App, HttpRequest, HttpResponse};
+use serde_json::{from_slice, json, Value as JsonValue};
+
+async fn links(req: HttpRequest) -> HttpResponse {
+    let res = HalResource::new(())
+        .with_route_link("self", &req, "order", &["12"])
+        .unwrap()
+        .with_route_link("item", &req, "item", &["12"])
+        .unwrap()
+        .with_route_link("files", &req, "files", &[] as &[&str])
+        .unwrap()
+        .with_route_link("tag", &req, "tag", &[] as &[&str])
+        .unwrap()
+        .with_route_link("code", &req, "code", &["12"])
+        .unwrap();
+    assert!(HalLink::from_route(&req, "nope", &["1"]).is_err());
+    HttpResponse::Ok().body(serde_json::to_string(&res).unwrap())
+}
+
+#[actix_web::test]
+async fn check_links_follow_actix_routes() {
+    let app = test::init_service(
+        App::new().service(
+            web::scope("/api")
+                .service(web::resource("/orders/{id}").name("order").to(links))
+                .service(web::resource("/orders/{id}/items/{item:\\d+}").name("item").to(links))
+                .service(web::resource("/files/{tail}*").name("files").to(links))
+                .service(web::resource("/tags/{slug:[a-z]+}").name("tag").to(links))
+                .service(web::resource("/codes/{id}/{code:[A-Z]{3}}").name("code").to(links)),
+        ),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri("/api/orders/12")
+        .insert_header(("Host", "shop.com"))
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(
+        from_slice::<JsonValue>(&body).unwrap(),
+        json!({
+            "_links": {
+                "files": {"href": "http://shop.com/api/files/{+tail}", "templated": true},
+                "item": {"href": "http://shop.com/api/orders/12/items/{item}", "templated": true},
+                "self": {"href": "http://shop.com/api/orders/12"},
+                "tag": {"href": "http://shop.com/api/tags/{slug}", "templated": true},
+                "code": {"href": "http://shop.com/api/codes/12/{segment1}", "templated": true}
+            }
+        })
+    );
+}
@@ -0,0 +1,67 @@
+use super::super::batch::BatchEmbed;
+use super::super::{HalError, HalResource};
+use futures::executor::block_on;
+use serde_json::json;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+fn orders() -> Vec<HalResource> {
+    vec![
+        HalResource::new(json!({"id": 1, "customer_id": 7})),
+        HalResource::new(json!({"id": 2, "customer_id": 8})),
+        HalResource::new(json!({"id": 3, "customer_id": 7})),
+        HalResource::new(json!({"id": 4})),
+    ]
+}
+
+fn customers(ids: Vec<u64>) -> HashMap<u64, HalResource> {
+    ids.into_iter()
+        .filter(|id| *id != 8)
+        .map(|id| (id, HalResource::new(json!({ "id": id }))))
+        .collect()
+}
+
+fn batch() -> BatchEmbed<impl Fn(&HalResource) -> Option<u64>> {
+    BatchEmbed::new("customer", |order: &HalResource| {
+        order.get_extra_data::<u64>("customer_id").ok()
+    })
+}
+
+#[test]
+fn check_keys_get_deduplicated_and_loaded_once() {
+    let calls = Cell::new(0);
+    let items = batch()
+        .load(orders(), |ids| {
+            calls.set(calls.get() + 1);
+            assert_eq!(ids, vec![7, 8]);
+            Ok(customers(ids))
+        })
+        .unwrap();
+    assert_eq!(calls.get(), 1);
+    assert_eq!(
+        items[2].get_resource("customer").unwrap().get_extra_data::<u64>("id").unwrap(),
+        7
+    );
+    assert!(items[1].get_resource("customer").is_none());
+    assert!(items[3].get_resource("customer").is_none());
+}
+
+#[test]
+fn check_loader_errors_get_propagated() {
+    let res = batch().load(orders(), |_| -> Result<HashMap<u64, HalResource>, _> {
+        Err(HalError::Custom("down".to_string()))
+    });
+    assert!(res.is_err());
+}
+
+#[test]
+fn check_embedded_collection_gets_loaded_asynchronously() {
+    let collection = HalResource::new(()).with_resources("items", orders());
+    let collection = block_on(batch().load_embedded_async(collection, "items", |ids| async {
+        Ok(customers(ids))
+    }))
+    .unwrap();
+    let items = collection.get_resources("items").unwrap();
+    assert!(items[0].get_resource("customer").is_some());
+    assert!(items[2].get_resource("customer").is_some());
+}
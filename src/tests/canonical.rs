@@ -0,0 +1,81 @@
+use super::super::canonical::if_none_match;
+use super::super::{HalResource, HalResponse};
+use serde_json::{from_str, json};
+
+#[test]
+fn check_key_order_does_not_change_canonical_form() {
+    let a: HalResource = from_str(r#"{"b":1,"a":{"y":[1,{"d":1,"c":2}],"x":null},"_links":{"self":{"href":"/a"}}}"#).unwrap();
+    let b = HalResource::new(json!({"a": {"x": null, "y": [1, {"c": 2, "d": 1}]}, "b": 1}))
+        .with_link("self", "/a");
+    assert_eq!(a.to_canonical_string().unwrap(), b.to_canonical_string().unwrap());
+    assert_eq!(a.etag().unwrap(), b.etag().unwrap());
+}
+
+#[test]
+fn check_link_forms_get_normalized() {
+    let a: HalResource = from_str(
+        r#"{"_links":{"self":[{"href":"/a"}],"curies":{"href":"/r/{rel}","name":"r","templated":true}},"_embedded":{"x":{"_links":{"up":[{"href":"/a"}]}}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        a.to_canonical_string().unwrap(),
+        r#"{"_embedded":{"x":{"_links":{"up":{"href":"/a"}}}},"_links":{"curies":[{"href":"/r/{rel}","name":"r","templated":true}],"self":{"href":"/a"}}}"#
+    );
+}
+
+#[test]
+fn check_different_resources_get_different_etags() {
+    let a = HalResource::new(json!({"v": 1}));
+    let b = HalResource::new(json!({"v": 2}));
+    assert_ne!(a.etag().unwrap(), b.etag().unwrap());
+    assert_eq!(a.etag().unwrap().len(), 68);
+    assert!(a.etag().unwrap().starts_with("W/\""));
+}
+
+#[test]
+fn check_if_none_match_comparison() {
+    assert!(if_none_match(r#""a", W/"b""#, r#""b""#));
+    assert!(if_none_match("*", r#""b""#));
+    assert!(!if_none_match(r#""a""#, r#""b""#));
+}
+
+#[test]
+fn check_response_answers_not_modified() {
+    let res = HalResource::new(json!({"v": 1}));
+    let etag = res.etag().unwrap();
+    let rendered = HalResponse::new(res.clone()).render();
+    assert!(rendered.headers.contains(&("ETag".to_string(), etag.clone())));
+    assert_eq!(rendered.status, 200);
+
+    let rendered = HalResponse::new(res.clone()).with_if_none_match(Some(etag)).render();
+    assert_eq!(rendered.status, 304);
+    assert!(rendered.body.is_empty());
+
+    let old = Some("\"old\"".to_string());
+    let rendered = HalResponse::new(res.clone()).with_if_none_match(old).render();
+    assert_eq!(rendered.status, 200);
+
+    let rendered = HalResponse::new(res).with_if_none_match(None).render();
+    assert_eq!(rendered.status, 200);
+}
+
+#[test]
+fn check_etag_is_weak_across_link_forms() {
+    let one: HalResource = from_str(r#"{"_links":{"self":{"href":"/a"}}}"#).unwrap();
+    let many: HalResource = from_str(r#"{"_links":{"self":[{"href":"/a"}]}}"#).unwrap();
+    assert_ne!(serde_json::to_string(&one).unwrap(), serde_json::to_string(&many).unwrap());
+    assert_eq!(one.etag().unwrap(), many.etag().unwrap());
+    assert!(one.etag().unwrap().starts_with("W/"));
+}
+
+#[test]
+fn check_explicit_templated_false_does_not_change_canonical_form() {
+    let a: HalResource = from_str(
+        r#"{"_links":{"self":{"href":"/a","templated":false},"up":[{"href":"/","templated":false},{"href":"/b"}]}}"#,
+    )
+    .unwrap();
+    let b: HalResource =
+        from_str(r#"{"_links":{"self":{"href":"/a"},"up":[{"href":"/"},{"href":"/b"}]}}"#).unwrap();
+    assert_eq!(a.to_canonical_string().unwrap(), b.to_canonical_string().unwrap());
+    assert_eq!(a.etag().unwrap(), b.etag().unwrap());
+}
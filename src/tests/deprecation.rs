@@ -0,0 +1,143 @@
+use super::super::deprecation::{self, Deprecation, DeprecationObserver, StrictObserver};
+use super::super::{HalError, HalLink, HalResource, HalResponse, HalResult};
+use serde_json::{json, to_value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Held by the tests installing a process-wide observer.
+static OBSERVER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Counts the lookups of `href` only, so that other tests reading deprecated
+/// links in parallel do not add up.
+struct Counter(&'static str, Arc<AtomicUsize>);
+
+impl DeprecationObserver for Counter {
+    fn on_deprecated(&self, _rel: &str, link: &HalLink) -> HalResult<()> {
+        if link.href == self.0 {
+            self.1.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+fn resource() -> HalResource {
+    HalResource::new(())
+        .with_link("self", "https://self.com")
+        .with_link(
+            "old",
+            HalLink::new("https://self.com/old").with_deprecation("https://docs.com/old"),
+        )
+}
+
+#[test]
+fn check_strict_observer_rejects_deprecated_link() {
+    let res = resource();
+    match res.follow_with("old", &StrictObserver) {
+        Err(HalError::DeprecatedLink(rel, url)) => {
+            assert_eq!(rel, "old");
+            assert_eq!(url, "https://docs.com/old");
+        }
+        _ => panic!("deprecated link was not rejected"),
+    }
+    assert!(res.follow_with("self", &StrictObserver).is_ok());
+}
+
+#[test]
+fn check_follow_fails_on_missing_link() {
+    assert!(resource().follow("missing").is_err());
+}
+
+#[test]
+fn check_installed_observer_gets_notified() {
+    let _lock = OBSERVER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let href = "https://self.com/counted";
+    let count = Arc::new(AtomicUsize::new(0));
+    deprecation::set_observer(Counter(href, count.clone()));
+    let counted = HalLink::new(href).with_deprecation("https://docs.com/counted");
+    let res = resource().with_link("counted", counted);
+    res.get_link("counted");
+    res.get_link("self");
+    res.get_links("counted");
+    res.follow("counted").unwrap();
+    deprecation::reset_observer();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn check_rejected_link_is_still_read() {
+    let _lock = OBSERVER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    deprecation::set_observer(StrictObserver);
+    let res = resource();
+    let link = res.get_link("old").map(|l| l.href.clone());
+    let links = res.get_links("old").map(|l| l.len());
+    let followed = res.follow("old").is_err();
+    deprecation::reset_observer();
+    assert_eq!(link.as_deref(), Some("https://self.com/old"));
+    assert_eq!(links, Some(1));
+    assert!(followed);
+}
+
+#[test]
+fn check_deprecation_notice_gets_headers() {
+    let date = UNIX_EPOCH + Duration::from_secs(1_688_169_599);
+    let sunset = UNIX_EPOCH + Duration::from_secs(1_735_689_600);
+    let notice = Deprecation::new()
+        .with_date(date)
+        .with_sunset(sunset)
+        .with_info("https://docs.com/v1")
+        .with_replacement("v2:orders");
+    let response = HalResponse::new(HalResource::new(())).with_deprecation(notice);
+    assert_eq!(
+        response.headers(),
+        vec![
+            ("Deprecation".to_string(), "@1688169599".to_string()),
+            ("Sunset".to_string(), "Wed, 01 Jan 2025 00:00:00 GMT".to_string()),
+            (
+                "Link".to_string(),
+                r#"<https://docs.com/v1>; rel="deprecation"; type="text/html""#.to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        Deprecation::new().headers(),
+        vec![("Deprecation".to_string(), "true".to_string())]
+    );
+}
+
+#[test]
+fn check_link_carries_sunset_date() {
+    let sunset = UNIX_EPOCH + Duration::from_secs(1_735_689_600);
+    let notice = Deprecation::new()
+        .with_sunset(sunset)
+        .with_info("https://docs.com/v1")
+        .with_replacement("v2:orders");
+    let link = HalLink::new("/v1/orders").with_deprecation_notice(&notice);
+    assert_eq!(
+        to_value(&link).unwrap(),
+        json!({
+            "href": "/v1/orders",
+            "deprecation": "https://docs.com/v1",
+            "replacement": "v2:orders",
+            "sunset": "Wed, 01 Jan 2025 00:00:00 GMT"
+        })
+    );
+    assert_eq!(link.sunset(), Some(sunset));
+    assert!(!link.is_sunset(sunset - Duration::from_secs(1)));
+    assert!(link.is_sunset(sunset));
+    assert!(notice.is_sunset(sunset));
+    assert!(!HalLink::new("/v2/orders").is_sunset(SystemTime::now()));
+}
+
+#[test]
+fn check_sunset_dates_get_clamped() {
+    let before = UNIX_EPOCH - Duration::from_secs(86_400);
+    let link = HalLink::new("/v0").with_sunset(before);
+    assert_eq!(link.extension::<String>("sunset").unwrap(), "Thu, 01 Jan 1970 00:00:00 GMT");
+    assert_eq!(link.sunset(), Some(UNIX_EPOCH));
+
+    let after = UNIX_EPOCH + Duration::from_secs(300_000_000_000);
+    let headers = Deprecation::new().with_date(before).with_sunset(after).headers();
+    assert_eq!(headers[0], ("Deprecation".to_string(), "@-86400".to_string()));
+    assert_eq!(headers[1], ("Sunset".to_string(), "Fri, 31 Dec 9999 23:59:59 GMT".to_string()));
+}
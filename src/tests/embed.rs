@@ -0,0 +1,79 @@
+use super::super::embed::{EmbedResolver, EmbedSpec};
+use super::super::{HalError, HalResource};
+use futures::executor::block_on;
+use serde_json::json;
+
+fn order() -> HalResource {
+    HalResource::new(json!({"id": 12}))
+        .with_link("self", "/orders/12")
+        .with_link("customer", "/customers/3")
+        .with_link("items", "/orders/12/items")
+}
+
+fn resolver() -> EmbedResolver {
+    let items = EmbedResolver::new().with_loader("product", |item: &HalResource| {
+        let sku = item.get_extra_data::<String>("sku")?;
+        Ok(HalResource::new(json!({ "sku": sku, "name": "Widget" })))
+    });
+    EmbedResolver::new()
+        .with_loader("customer", |_: &HalResource| {
+            Ok(HalResource::new(json!({"name": "Ada"})).with_link("self", "/customers/3"))
+        })
+        .with_async_loader("items", |_: &HalResource| async {
+            Ok(vec![
+                HalResource::new(json!({"sku": "a"})),
+                HalResource::new(json!({"sku": "b"})),
+            ])
+        })
+        .with_nested("items", items)
+}
+
+#[test]
+fn check_spec_gets_parsed_from_query() {
+    let spec = EmbedSpec::from_query("page=2&embed=customer%2Citems.product&zoom=a.b.c.d");
+    assert!(spec.contains("customer"));
+    assert!(spec.nested("items").unwrap().contains("product"));
+    let mut depth = 0;
+    let mut node = spec.nested("a");
+    while let Some(n) = node {
+        depth += 1;
+        node = n.rels().next().and_then(|r| n.nested(r));
+    }
+    assert_eq!(depth, 3);
+    assert!(EmbedSpec::from_query("page=2").is_empty());
+}
+
+#[test]
+fn check_only_requested_rels_get_embedded() {
+    let res = resolver()
+        .resolve(order(), &EmbedSpec::parse("customer"))
+        .unwrap();
+    assert_eq!(
+        res.get_resource("customer").unwrap().get_extra_data::<String>("name").unwrap(),
+        "Ada"
+    );
+    assert!(res.get_resources("items").is_none());
+    assert!(res.get_link("items").is_some());
+}
+
+#[test]
+fn check_async_loader_fails_in_sync_resolve() {
+    match resolver().resolve(order(), &EmbedSpec::parse("items")) {
+        Err(HalError::Custom(_)) => {}
+        _ => panic!("async loader should not run synchronously"),
+    }
+}
+
+#[test]
+fn check_nested_paths_get_embedded_asynchronously() {
+    let resolver = resolver();
+    let spec = EmbedSpec::parse("customer,items.product");
+    let res = block_on(resolver.resolve_async(order(), &spec)).unwrap();
+    let items = res.get_resources("items").unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(
+        items[1].get_resource("product").unwrap().get_extra_data::<String>("sku").unwrap(),
+        "b"
+    );
+    assert!(res.get_resource("customer").is_some());
+}
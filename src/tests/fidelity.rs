@@ -0,0 +1,56 @@
+use super::super::HalResource;
+use serde_json::{from_str, to_string};
+
+#[test]
+fn check_array_forms_survive_a_round_trip() {
+    let source = r#"{"_links":{"self":[{"href":"/a"}],"up":{"href":"/"}},"_embedded":{"items":[{"id":1}]}}"#;
+    let res: HalResource = from_str(source).unwrap();
+    assert_eq!(to_string(&res).unwrap(), source);
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn check_documents_survive_a_round_trip_unchanged() {
+    let source = r#"{"_links":{"self":{"href":"/a"},"curies":[{"href":"/r/{rel}","templated":true,"name":"r"}],"r:b":{"href":"/b"}},"_embedded":{"z":{"_links":{"self":{"href":"/z"}},"y":1,"x":2},"a":[]},"zeta":1,"alpha":{"d":1,"c":2}}"#;
+    let res: HalResource = from_str(source).unwrap();
+    assert_eq!(to_string(&res).unwrap(), source);
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn check_rels_keep_insertion_order() {
+    let res = HalResource::new(serde_json::json!({"b": 1, "a": 2}))
+        .with_link("self", "/a")
+        .with_link("next", "/b")
+        .with_link("first", "/c");
+    assert_eq!(
+        to_string(&res).unwrap(),
+        r#"{"_links":{"self":{"href":"/a"},"next":{"href":"/b"},"first":{"href":"/c"}},"b":1,"a":2}"#
+    );
+}
+
+#[test]
+fn check_explicit_templated_false_survives_a_round_trip() {
+    let source = r#"{"_links":{"next":{"href":"/b"},"self":{"href":"/a","templated":false}}}"#;
+    let res: HalResource = from_str(source).unwrap();
+    assert_eq!(to_string(&res).unwrap(), source);
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn check_payload_first_documents_survive_a_round_trip_unchanged() {
+    let source = r#"{"id":7,"_embedded":{"items":[{"name":"x","_links":{"self":{"href":"/x"}}}]},"name":"a","_links":{"self":{"href":"/a"}}}"#;
+    let res: HalResource = from_str(source).unwrap();
+    assert_eq!(to_string(&res).unwrap(), source);
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn check_new_keys_follow_the_original_ones() {
+    let res: HalResource = from_str(r#"{"id":7,"_links":{"self":{"href":"/a"}}}"#).unwrap();
+    let res = res.with_link("next", "/b").with_resource("item", HalResource::new(serde_json::json!({})));
+    assert_eq!(
+        to_string(&res).unwrap(),
+        r#"{"id":7,"_links":{"self":{"href":"/a"},"next":{"href":"/b"}},"_embedded":{"item":{}}}"#
+    );
+}
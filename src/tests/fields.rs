@@ -0,0 +1,49 @@
+use super::super::fields::Fieldset;
+use super::super::HalResource;
+use serde_json::{from_str, json, to_value, Value as JsonValue};
+
+fn order() -> HalResource {
+    let product = HalResource::new(json!({"sku": "a", "name": "Widget", "weight": 3}))
+        .with_link("self", "/products/a");
+    let item = HalResource::new(json!({"sku": "a", "qty": 1, "price": 30}))
+        .with_link("self", "/orders/12/items/1")
+        .with_link("product", "/products/a")
+        .with_resource("product", product);
+    HalResource::new(json!({"id": 12, "total": 30, "notes": "fragile"}))
+        .with_link("self", "/orders/12")
+        .with_link("cancel", "/orders/12/cancel")
+        .with_curie("acme", "/rels/{rel}")
+        .with_resource("items", item)
+}
+
+#[test]
+fn check_query_gets_parsed() {
+    let fields = Fieldset::from_query("fields=id,total&fields%5Bitems%5D=sku&page=2");
+    assert_eq!(fields.fields("").unwrap().len(), 2);
+    assert!(fields.fields("items").unwrap().contains("sku"));
+    assert!(fields.fields("customer").is_none());
+    assert!(Fieldset::from_query("page=2").is_empty());
+}
+
+#[test]
+fn check_nested_fieldsets_get_applied() {
+    let fields = Fieldset::new()
+        .with_fields("", vec!["id"])
+        .with_fields("items.product", vec!["name"]);
+    let res = order().project(&fields);
+    assert_eq!(
+        to_value(res).unwrap(),
+        from_str::<JsonValue>(r#"{"_links":{"cancel":{"href":"/orders/12/cancel"},"curies":[{"href":"/rels/{rel}","templated":true,"name":"acme"}],"self":{"href":"/orders/12"}},"_embedded":{"items":{"_links":{"product":{"href":"/products/a"},"self":{"href":"/orders/12/items/1"}},"_embedded":{"product":{"_links":{"self":{"href":"/products/a"}},"name":"Widget"}},"price":30,"qty":1,"sku":"a"}},"id":12}"#).unwrap()
+    );
+}
+
+#[test]
+fn check_non_essential_links_get_stripped() {
+    let res = order().project(&Fieldset::new().strip_links(true));
+    assert!(res.get_link("cancel").is_none());
+    assert!(res.get_link("curies").is_some());
+    let item = res.get_resource("items").unwrap();
+    assert!(item.get_link("product").is_none());
+    assert!(item.get_self().is_some());
+    assert_eq!(item.get_extra_data::<u32>("price").unwrap(), 30);
+}
@@ -0,0 +1,134 @@
+use super::super::forwarded::{RequestOrigin, TrustedProxies};
+use super::super::{HalLink, HalResource, HalResponse};
+use std::net::IpAddr;
+
+fn ip(s: &str) -> Option<IpAddr> {
+    s.parse().ok()
+}
+
+#[test]
+fn check_proxy_allowlist() {
+    let proxies = TrustedProxies::new()
+        .with_proxy("10.0.0.0/8")
+        .unwrap()
+        .with_proxy("2001:db8::1")
+        .unwrap();
+    assert!(proxies.trusts(ip("10.200.0.1")));
+    assert!(proxies.trusts(ip("::ffff:10.0.0.1")));
+    assert!(proxies.trusts(ip("2001:db8::1")));
+    assert!(!proxies.trusts(ip("11.0.0.1")));
+    assert!(!proxies.trusts(ip("2001:db8::2")));
+    assert!(!proxies.trusts(None));
+    assert!(TrustedProxies::any().trusts(None));
+    assert!(TrustedProxies::new().with_proxy("10.0.0.0/33").is_err());
+    assert!(TrustedProxies::new().with_proxy("nope").is_err());
+}
+
+#[test]
+fn check_spoofed_headers_are_ignored() {
+    let headers = vec![("host", "svc:8080"), ("x-forwarded-host", "evil.com")];
+    let proxies = TrustedProxies::new().with_proxy("10.0.0.1").unwrap();
+    let origin = RequestOrigin::from_headers(headers, "http", ip("192.168.1.1"), &proxies);
+    assert_eq!(origin.base().unwrap(), "http://svc:8080/");
+}
+
+#[test]
+fn check_forwarded_header_wins() {
+    let headers = vec![
+        ("Host", "svc"),
+        ("Forwarded", r#"for=192.0.2.60;proto=HTTPS;host="api.acme.com", for=10.0.0.2"#),
+        ("X-Forwarded-Host", "other.com"),
+        ("X-Forwarded-Prefix", "shop/"),
+    ];
+    let origin = RequestOrigin::from_headers(headers, "http", None, &TrustedProxies::any());
+    assert_eq!(origin.base().unwrap(), "https://api.acme.com/shop/");
+}
+
+#[test]
+fn check_spoofed_entries_before_trusted_proxies_are_ignored() {
+    let proxies = TrustedProxies::new().with_proxy("10.0.0.0/8").unwrap();
+    let headers = vec![
+        ("Host", "svc"),
+        (
+            "Forwarded",
+            r#"for=1.2.3.4;proto=http;host=evil.com, for="203.0.113.7:5000";proto=https;host=api.acme.com, for=10.0.0.2"#,
+        ),
+    ];
+    let origin = RequestOrigin::from_headers(headers, "http", ip("10.0.0.3"), &proxies);
+    assert_eq!(origin.base().unwrap(), "https://api.acme.com/");
+
+    let headers = vec![
+        ("Host", "svc"),
+        ("X-Forwarded-For", "1.2.3.4, 203.0.113.7, 10.0.0.2"),
+        ("X-Forwarded-Proto", "http, https, http"),
+        ("X-Forwarded-Host", "evil.com, api.acme.com, svc.internal"),
+        ("X-Forwarded-Prefix", "/shop"),
+    ];
+    let origin = RequestOrigin::from_headers(headers, "http", ip("10.0.0.3"), &proxies);
+    assert_eq!(origin.base().unwrap(), "https://api.acme.com/shop/");
+
+    let headers = vec![("Host", "svc"), ("X-Forwarded-Host", "evil.com, api.acme.com")];
+    let origin = RequestOrigin::from_headers(headers, "http", ip("10.0.0.3"), &proxies);
+    assert_eq!(origin.host, "api.acme.com");
+}
+
+#[test]
+fn check_spoofed_header_lines_are_ignored() {
+    let proxies = TrustedProxies::new().with_proxy("10.0.0.0/8").unwrap();
+    let headers = vec![
+        ("Host", "svc"),
+        ("Forwarded", "for=1.2.3.4;proto=http;host=evil.com"),
+        ("Forwarded", "for=203.0.113.7;proto=https;host=api.acme.com"),
+    ];
+    let origin = RequestOrigin::from_headers(headers, "http", ip("10.0.0.3"), &proxies);
+    assert_eq!(origin.base().unwrap(), "https://api.acme.com/");
+
+    let headers = vec![
+        ("Host", "svc"),
+        ("X-Forwarded-For", "1.2.3.4"),
+        ("X-Forwarded-Proto", "http"),
+        ("X-Forwarded-Host", "evil.com"),
+        ("X-Forwarded-Prefix", "/evil"),
+        ("X-Forwarded-For", "203.0.113.7"),
+        ("X-Forwarded-Proto", "https"),
+        ("X-Forwarded-Host", "api.acme.com"),
+        ("X-Forwarded-Prefix", "/shop"),
+    ];
+    let origin = RequestOrigin::from_headers(headers, "http", ip("10.0.0.3"), &proxies);
+    assert_eq!(origin.base().unwrap(), "https://api.acme.com/shop/");
+}
+
+#[test]
+fn check_default_scheme_is_configurable() {
+    let proxies = TrustedProxies::new();
+    assert_eq!(proxies.scheme(), "http");
+    let proxies = proxies.with_scheme("HTTPS");
+    assert_eq!(proxies.scheme(), "https");
+    let origin = RequestOrigin::from_headers(vec![("Host", "svc")], proxies.scheme(), None, &proxies);
+    assert_eq!(origin.base().unwrap(), "https://svc/");
+}
+
+#[test]
+fn check_response_links_get_absolute() {
+    let headers = vec![("Host", "svc"), ("X-Forwarded-Proto", "http, https")];
+    let origin = RequestOrigin::from_headers(headers, "http", ip("127.0.0.1"), &TrustedProxies::any());
+    let res = HalResource::new(())
+        .with_link("self", "/orders/12")
+        .with_link("items", "items{?page}")
+        .with_link("search", HalLink::new("{+base}/search").templated(true))
+        .with_link("up", "../orders")
+        .with_link("docs", "https://docs.com/orders")
+        .with_link("cdn", "//cdn.com/orders.css");
+    let response = HalResponse::new(res).with_origin(&origin);
+    let res = response.resource();
+    assert_eq!(res.get_self().unwrap().href, "https://svc/orders/12");
+    assert_eq!(res.get_link("items").unwrap().href, "items{?page}");
+    assert_eq!(res.get_link("up").unwrap().href, "../orders");
+    assert_eq!(res.get_link("search").unwrap().href, "{+base}/search");
+    assert_eq!(res.get_link("docs").unwrap().href, "https://docs.com/orders");
+    assert_eq!(res.get_link("cdn").unwrap().href, "//cdn.com/orders.css");
+
+    let unknown = RequestOrigin::default();
+    assert!(unknown.base().is_none());
+    assert_eq!(unknown.absolutize_href("/orders"), "/orders");
+}
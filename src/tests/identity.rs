@@ -0,0 +1,67 @@
+use super::super::identity::Identity;
+use super::super::{HalLink, HalResource};
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+fn hash_of<T: Hash>(t: &T) -> u64 {
+    let mut h = DefaultHasher::new();
+    t.hash(&mut h);
+    h.finish()
+}
+
+#[test]
+fn check_resources_without_self_link_compare_structurally() {
+    let a = HalResource::new(json!({"id": 1}));
+    let b = HalResource::new(json!({"id": 2}));
+    assert!(a != b);
+    assert!(a == HalResource::new(json!({"id": 1})));
+    assert_eq!(hash_of(&a), hash_of(&HalResource::new(json!({"id": 1}))));
+}
+
+#[test]
+fn check_self_link_identity_ignores_payload() {
+    let a = HalResource::new(json!({"v": 1})).with_link("self", "/a");
+    let b = HalResource::new(json!({"v": 2})).with_link("self", "/a");
+    assert!(a == b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+    assert!(!a.structural_eq(&b));
+    assert!(!a.identity_eq(&b, &Identity::Full));
+}
+
+#[test]
+fn check_structural_eq_covers_links_and_embedded() {
+    let child = |v: i32| HalResource::new(json!({ "v": v }));
+    let a = HalResource::new(()).with_link("self", "/a").with_resource("c", child(1));
+    let b = HalResource::new(()).with_link("self", "/a").with_resource("c", child(2));
+    assert!(!a.structural_eq(&b));
+    let c = HalResource::new(())
+        .with_link("self", HalLink::new("/a").with_title("A"))
+        .with_resource("c", child(1));
+    assert!(!a.structural_eq(&c));
+    assert!(a.structural_eq(&a.clone()));
+}
+
+#[test]
+fn check_payload_key_identity_deduplicates() {
+    let by_id = Identity::PayloadKey("id".to_string());
+    let items = [
+        HalResource::new(json!({"id": 1, "rev": 1})),
+        HalResource::new(json!({"id": 1, "rev": 2})),
+        HalResource::new(json!({"id": 2})),
+        HalResource::new(json!({"name": "x"})),
+        HalResource::new(json!({"name": "x"})),
+    ];
+    let unique: HashSet<_> = items.iter().map(|r| r.identified(&by_id)).collect();
+    assert_eq!(unique.len(), 3);
+}
+
+#[test]
+fn check_link_structural_eq_and_hash() {
+    let a = HalLink::new("/a").with_title("A");
+    let b = HalLink::new("/a");
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+    assert!(!a.structural_eq(&b));
+}
@@ -0,0 +1,128 @@
+use super::super::forwarded::TrustedProxies;
+use super::super::layer::{HalBody, HalLayer};
+use super::super::link_policy::LinkDecision;
+use super::super::{HalLink, HalResource};
+use bytes::Bytes;
+use futures::executor::block_on;
+use http1::{header, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use serde_json::{json, to_string};
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+#[derive(Clone)]
+struct Backend;
+
+impl Service<Request<()>> for Backend {
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<()>) -> Self::Future {
+        let (content_type, body) = if req.uri().path() == "/plain" {
+            ("text/plain", "hello".to_string())
+        } else {
+            let order = HalResource::new(json!({"id": 12, "total": 30}))
+                .with_link("self", "/orders/12")
+                .with_link("cancel", "/orders/12/cancel");
+            ("application/hal+json", to_string(&order).unwrap())
+        };
+        ready(Ok(Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Full::new(Bytes::from(body)))
+            .unwrap()))
+    }
+}
+
+fn call(layer: &HalLayer, req: Request<()>) -> (Response<()>, String) {
+    let mut service = layer.layer(Backend);
+    let response: Response<HalBody<Full<Bytes>>> = block_on(service.call(req)).unwrap();
+    let (parts, body) = response.into_parts();
+    let body = block_on(body.collect()).unwrap().to_bytes();
+    (Response::from_parts(parts, ()), String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[test]
+fn check_pipeline_rewrites_hal_responses() {
+    let layer = HalLayer::new()
+        .with_link_policy(
+            |admin: &bool, rel: &str, _: &HalLink| match rel {
+                "cancel" if !admin => LinkDecision::Drop,
+                _ => LinkDecision::Keep,
+            },
+            |req: &Request<()>| req.headers().contains_key("x-admin"),
+        )
+        .with_fieldsets(true)
+        .with_origin(TrustedProxies::any());
+    let req = Request::builder()
+        .uri("/orders/12?fields=id")
+        .header(header::HOST, "svc")
+        .header("x-forwarded-proto", "https")
+        .body(())
+        .unwrap();
+    let (response, body) = call(&layer, req);
+    assert_eq!(body, r#"{"_links":{"self":{"href":"https://svc/orders/12"}},"id":12}"#);
+    assert!(response.headers().get(header::CONTENT_LENGTH).is_none());
+    assert!(response.headers().contains_key(header::ETAG));
+}
+
+#[test]
+fn check_other_responses_pass_through() {
+    let req = Request::builder().uri("/plain").body(()).unwrap();
+    let (response, body) = call(&HalLayer::new(), req);
+    assert_eq!(body, "hello");
+    assert!(response.headers().get(header::ETAG).is_none());
+    assert_eq!(response.headers()[header::CONTENT_LENGTH], "5");
+}
+
+#[test]
+fn check_etag_turns_into_not_modified() {
+    let (response, _) = call(&HalLayer::new(), Request::new(()));
+    let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+    let req = Request::builder().header(header::IF_NONE_MATCH, etag).body(()).unwrap();
+    let (response, body) = call(&HalLayer::new(), req);
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert!(body.is_empty());
+}
+
+#[test]
+fn check_content_gets_negotiated() {
+    let layer = HalLayer::new().with_format("text/uri-list", |res: &HalResource| {
+        Ok(res.get_links("self").into_iter().flatten().map(|l| l.href.clone()).collect())
+    });
+    let req = Request::builder()
+        .header(header::ACCEPT, "application/hal+json;q=0.5, text/*")
+        .body(())
+        .unwrap();
+    let (response, body) = call(&layer, req);
+    assert_eq!(body, "/orders/12");
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/uri-list");
+    assert_eq!(response.headers()[header::VARY], "Accept");
+
+    let req = Request::builder().header(header::ACCEPT, "*/*").body(()).unwrap();
+    let (response, _) = call(&layer, req);
+    assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("application/hal+json"));
+
+    let req = Request::builder()
+        .header(header::ACCEPT, "text/uri-list;q=1, */*;q=0.1")
+        .body(())
+        .unwrap();
+    let (response, body) = call(&layer, req);
+    assert_eq!(body, "/orders/12");
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/uri-list");
+
+    let req = Request::builder()
+        .header(header::ACCEPT, "*/*, application/hal+json;q=0")
+        .body(())
+        .unwrap();
+    let (response, _) = call(&layer, req);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/uri-list");
+}
@@ -1,6 +1,6 @@
 //use serde::de::Deserialize;
 use super::super::link::HalLink;
-use serde_json::from_str;
+use serde_json::{from_str, json, to_string};
 
 #[test]
 fn ensure_href_gets_deserialized() {
@@ -40,3 +40,52 @@ fn ensure_full_link_gets_deserialized() {
     );
     assert_eq!(link.title, Some("Google Search".to_string()));
 }
+
+#[test]
+fn check_extensions_survive_a_round_trip() {
+    let source = r#"{"href":"/orders/12","title":"Order","method":"DELETE","x-permission":{"roles":["admin"]}}"#;
+    let link: HalLink = from_str(source).unwrap();
+    assert_eq!(link.extension::<String>("method"), Some("DELETE".to_string()));
+    assert_eq!(link.extensions.len(), 2);
+    assert_eq!(to_string(&link).unwrap(), source);
+}
+
+#[test]
+fn check_type_survives_a_round_trip() {
+    let source = r#"{"href":"/docs","type":"text/html"}"#;
+    let link: HalLink = from_str(source).unwrap();
+    assert_eq!(link.media_type, Some("text/html".to_string()));
+    assert!(link.extensions.is_empty());
+    assert_eq!(to_string(&link).unwrap(), source);
+    let link = HalLink::new("/docs").with_extension("type", "text/plain");
+    assert_eq!(link.media_type, None);
+    assert!(link.extensions.is_empty());
+}
+
+#[test]
+fn check_old_media_type_documents_still_get_read() {
+    let link: HalLink = from_str(r#"{"href":"/docs","media_type":"text/html"}"#).unwrap();
+    assert_eq!(link.media_type, Some("text/html".to_string()));
+    assert!(link.extensions.is_empty());
+    assert_eq!(to_string(&link).unwrap(), r#"{"href":"/docs","type":"text/html"}"#);
+}
+
+#[test]
+fn check_extensions_get_typed_accessors() {
+    let link = HalLink::new("/orders/12")
+        .with_extension("retries", 3)
+        .with_extension("sunset", "2027-01-01")
+        .with_extension("href", "/elsewhere");
+    assert_eq!(link.extension::<u32>("retries"), Some(3));
+    assert_eq!(link.extension::<u32>("sunset"), None);
+    assert_eq!(link.extension::<bool>("missing"), None);
+    assert_eq!(
+        to_string(&link).unwrap(),
+        r#"{"href":"/orders/12","retries":3,"sunset":"2027-01-01"}"#
+    );
+    assert!(!link.structural_eq(&HalLink::new("/orders/12")));
+    let same = HalLink::new("/orders/12")
+        .with_extension("sunset", "2027-01-01")
+        .with_extension("retries", json!(3));
+    assert!(link.structural_eq(&same));
+}
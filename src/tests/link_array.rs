@@ -0,0 +1,59 @@
+use super::super::link_array::LinkArrayPolicy;
+use super::super::HalResource;
+use serde_json::{from_str, json, to_string, to_value, Value};
+
+#[test]
+fn check_policy_decides_the_form_of_new_rels() {
+    let policy = LinkArrayPolicy::new()
+        .with_always_array(vec!["item"])
+        .with_never_array(vec!["self", "orders"])
+        .with_default_array(true);
+    let res = HalResource::new(json!({}))
+        .with_link_array_policy(policy)
+        .with_link("self", "/")
+        .with_link("item", "/items/1")
+        .with_link("next", "/?page=2")
+        .with_resources("orders", vec![HalResource::new(json!({"id": 1}))])
+        .with_resource("customer", HalResource::new(json!({"id": 7})));
+    assert_eq!(
+        to_value(&res).unwrap(),
+        from_str::<Value>(r#"{"_links":{"item":[{"href":"/items/1"}],"next":[{"href":"/?page=2"}],"self":{"href":"/"}},"_embedded":{"customer":[{"id":7}],"orders":{"id":1}}}"#).unwrap()
+    );
+}
+
+#[test]
+fn check_policy_applies_to_existing_rels() {
+    let res = HalResource::new(json!({}))
+        .with_link("item", "/items/1")
+        .with_resources("orders", vec![HalResource::new(json!({"id": 1}))])
+        .with_link_array_policy(
+            LinkArrayPolicy::new()
+                .with_always_array(vec!["item"])
+                .with_never_array(vec!["orders"]),
+        );
+    assert_eq!(
+        to_string(&res).unwrap(),
+        r#"{"_links":{"item":[{"href":"/items/1"}]},"_embedded":{"orders":{"id":1}}}"#
+    );
+}
+
+#[test]
+fn check_unlisted_rels_keep_the_usual_forms() {
+    let res = HalResource::new(json!({}))
+        .with_link_array_policy(LinkArrayPolicy::new().with_never_array(vec!["curies"]))
+        .with_curie("acme", "/rels/{rel}")
+        .with_link("self", "/");
+    assert_eq!(
+        to_string(&res).unwrap(),
+        r#"{"_links":{"curies":[{"href":"/rels/{rel}","templated":true,"name":"acme"}],"self":{"href":"/"}}}"#
+    );
+}
+
+#[test]
+fn check_deserialized_forms_round_trip() {
+    let source = r#"{"_links":{"item":[{"href":"/items/1"}],"self":{"href":"/"}},"_embedded":{"orders":[{"id":1}],"owner":{"id":2}}}"#;
+    let res: HalResource = from_str(source).unwrap();
+    assert_eq!(to_string(&res).unwrap(), source);
+    let res = res.with_link("self", "/other");
+    assert!(to_string(&res).unwrap().contains(r#""self":[{"href":"/"},{"href":"/other"}]"#));
+}
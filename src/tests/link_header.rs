@@ -0,0 +1,112 @@
+use super::super::link_header::parse;
+use super::super::{HalLink, HalResource, HalResponse};
+
+#[test]
+fn check_links_get_formatted_with_parameters() {
+    // Added in relation order, so that the header is the same with the
+    // `preserve_order` feature.
+    let res = HalResource::new(())
+        .with_link(
+            "help",
+            HalLink::new("/docs")
+                .with_title("Say \"hi\"")
+                .with_media_type("text/html")
+                .with_hreflang("en")
+                .with_deprecation("/docs/why"),
+        )
+        .with_link("self", "/orders/12")
+        .with_link("search", HalLink::new("/orders{?q}").templated(true))
+        .with_curie("acme", "/rels/{rel}");
+    assert_eq!(
+        res.to_link_header(),
+        r#"</docs>; rel="help"; title="Say \"hi\""; type="text/html"; hreflang="en"; deprecation="/docs/why", </orders/12>; rel="self""#
+    );
+}
+
+#[test]
+fn check_header_gets_parsed() {
+    let links = parse(
+        r#"<https://api.com/orders?page=2>; rel="next last"; title="a, b; c", <https://api.com/x>; rel=self; type="application/hal+json""#,
+    )
+    .unwrap();
+    assert_eq!(links.len(), 3);
+    assert_eq!(links[0].0, "next");
+    assert_eq!(links[1].0, "last");
+    assert_eq!(links[1].1.href, "https://api.com/orders?page=2");
+    assert_eq!(links[1].1.title, Some("a, b; c".to_string()));
+    assert_eq!(links[2].0, "self");
+    assert_eq!(links[2].1.media_type, Some("application/hal+json".to_string()));
+}
+
+#[test]
+fn check_invalid_header_gets_rejected() {
+    assert!(parse(r#"https://api.com; rel="self""#).is_err());
+}
+
+#[test]
+fn check_header_round_trips_through_resource() {
+    let res = HalResource::new(())
+        .with_link("self", HalLink::new("/orders/12").with_title("Order \\ 12"))
+        .with_link("item", "/items/1")
+        .with_link("item", "/items/2");
+    let back = HalResource::new(()).with_link_header(&res.to_link_header()).unwrap();
+    assert_eq!(back.get_links("item").unwrap().len(), 2);
+    assert_eq!(back.get_self().unwrap().title, Some("Order \\ 12".to_string()));
+}
+
+#[test]
+fn check_response_emits_link_header_on_demand() {
+    let res = HalResource::new(()).with_link("self", "/orders/12");
+    assert!(HalResponse::new(res.clone()).headers().is_empty());
+    assert_eq!(
+        HalResponse::new(res).with_link_header(true).headers(),
+        vec![("Link".to_string(), r#"</orders/12>; rel="self""#.to_string())]
+    );
+}
+
+#[test]
+fn check_extensions_map_to_link_parameters() {
+    let link = HalLink::new("/orders/12")
+        .with_extension("method", "GET")
+        .with_extension("n", 1);
+    let res = HalResource::new(()).with_link("self", link);
+    assert_eq!(res.to_link_header(), r#"</orders/12>; rel="self"; method="GET""#);
+    let links = parse(r##"</orders/12>; rel="self"; rel="up"; Anchor="#x""##).unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].1.extension::<String>("anchor"), Some("#x".to_string()));
+}
+
+#[test]
+fn check_unsafe_characters_get_escaped() {
+    let link = HalLink::new("/orders/a b>c%20d")
+        .with_title("Café \"au lait\"")
+        .with_extension("bad name", "x")
+        .with_extension("note", "line\nbreak");
+    let res = HalResource::new(()).with_link("self", link);
+    let header = res.to_link_header();
+    assert_eq!(
+        header,
+        r#"</orders/a%20b%3Ec%20d>; rel="self"; title*=UTF-8''Caf%C3%A9%20%22au%20lait%22; note*=UTF-8''line%0Abreak"#
+    );
+    assert!(header.bytes().all(|b| b == b' ' || b.is_ascii_graphic()));
+
+    let links = parse(&header).unwrap();
+    assert_eq!(links[0].1.title, Some("Café \"au lait\"".to_string()));
+    assert_eq!(links[0].1.extension::<String>("note"), Some("line\nbreak".to_string()));
+    let links = parse(r#"</x>; rel="self"; title="Cafe"; title*=UTF-8'fr'Caf%C3%A9"#).unwrap();
+    assert_eq!(links[0].1.title, Some("Café".to_string()));
+}
+
+#[test]
+fn check_invalid_headers_are_left_out() {
+    let res = HalResource::new(()).with_link("self", HalLink::new("/x").with_title("Café"));
+    let headers = HalResponse::new(res)
+        .with_link_header(true)
+        .with_header("X-Note", "line\nbreak")
+        .with_header("X Bad", "ok")
+        .headers();
+    assert_eq!(
+        headers,
+        vec![("Link".to_string(), r#"</x>; rel="self"; title*=UTF-8''Caf%C3%A9"#.to_string())]
+    );
+}
@@ -0,0 +1,69 @@
+use super::super::link_policy::{LinkDecision, LinkPolicy};
+use super::super::{HalLink, HalResource, HalResponse};
+use serde_json::{json, to_string};
+
+struct Roles(Vec<&'static str>);
+
+struct AdminOnly;
+
+impl LinkPolicy<Roles> for AdminOnly {
+    fn decide(&self, ctx: &Roles, rel: &str, link: &HalLink) -> LinkDecision {
+        if ctx.0.contains(&"admin") {
+            LinkDecision::Keep
+        } else if rel == "edit" {
+            LinkDecision::Drop
+        } else if link.href.contains("?secret") {
+            LinkDecision::Rewrite(HalLink::new(link.href.replace("?secret", "")))
+        } else {
+            LinkDecision::Keep
+        }
+    }
+}
+
+fn order() -> HalResource {
+    HalResource::new(json!({"id": 12}))
+        .with_link("self", "/orders/12")
+        .with_link("edit", "/orders/12/edit")
+        .with_resource(
+            "items",
+            HalResource::new(json!({"sku": "a"}))
+                .with_link("self", "/items/1?secret")
+                .with_link("edit", "/items/1/edit"),
+        )
+}
+
+#[test]
+fn check_policy_runs_recursively() {
+    let res = order().apply_link_policy(&AdminOnly, &Roles(vec!["user"]));
+    assert_eq!(
+        to_string(&res).unwrap(),
+        r#"{"_links":{"self":{"href":"/orders/12"}},"_embedded":{"items":{"_links":{"self":{"href":"/items/1"}},"sku":"a"}},"id":12}"#
+    );
+    let res = order().apply_link_policy(&AdminOnly, &Roles(vec!["admin"]));
+    assert!(res.structural_eq(&order()));
+}
+
+#[test]
+fn check_closures_are_policies() {
+    let no_curies = |_: &(), rel: &str, _: &HalLink| match rel {
+        "curies" => LinkDecision::Drop,
+        _ => LinkDecision::Keep,
+    };
+    let res = HalResource::new(())
+        .with_curie("acme", "/rels/{rel}")
+        .with_link("acme:orders", "/orders")
+        .apply_link_policy(&no_curies, &());
+    assert_eq!(to_string(&res).unwrap(), r#"{"_links":{"acme:orders":{"href":"/orders"}}}"#);
+}
+
+#[test]
+fn check_response_applies_policy_before_rendering() {
+    let response = HalResponse::new(order())
+        .with_link_policy(&AdminOnly, &Roles(vec![]))
+        .with_link_header(true);
+    assert_eq!(
+        response.headers(),
+        vec![("Link".to_string(), r#"</orders/12>; rel="self""#.to_string())]
+    );
+    assert!(!response.render().body.contains("edit"));
+}
@@ -1,5 +1,33 @@
 include!("serde_types.in.rs");
 
+#[cfg(feature = "actix-web")]
+pub mod actix_route;
+pub mod batch;
+pub mod canonical;
+pub mod deprecation;
+pub mod embed;
+pub mod fidelity;
+pub mod fields;
+pub mod forwarded;
+pub mod identity;
+#[cfg(feature = "tower")]
+pub mod layer;
 pub mod link;
+pub mod link_array;
+pub mod link_header;
+pub mod link_policy;
 pub mod resource;
-pub mod one_or_many;
\ No newline at end of file
+pub mod route;
+pub mod sse;
+pub mod state_machine;
+pub mod one_or_many;
+pub mod patch;
+#[cfg(feature = "poem")]
+pub mod poem_response;
+pub mod reserved;
+pub mod problem;
+pub mod rel;
+#[cfg(feature = "rocket")]
+pub mod rocket_response;
+pub mod uri;
+pub mod visitor;
\ No newline at end of file
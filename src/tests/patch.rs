@@ -0,0 +1,80 @@
+use super::super::patch::{diff, PatchOp};
+use super::super::{HalError, HalResource};
+use serde_json::{from_value, json};
+
+fn order() -> HalResource {
+    HalResource::new(json!({
+        "status": "pending",
+        "tags": ["a", "b"],
+        "address": {"city": "Paris", "zip": "75001"}
+    }))
+    .with_link("self", "/orders/12")
+}
+
+#[test]
+fn check_merge_patch_follows_rfc7396() {
+    let mut res = order();
+    res.apply_merge_patch(&json!({"status": "paid", "address": {"zip": null}, "tags": ["c"]}))
+        .unwrap();
+    assert_eq!(
+        res.get_data::<serde_json::Value>().unwrap(),
+        json!({"status": "paid", "tags": ["c"], "address": {"city": "Paris"}})
+    );
+}
+
+#[test]
+fn check_patches_touching_reserved_keys_get_rejected() {
+    let mut res = order();
+    match res.apply_merge_patch(&json!({"_links": {"self": {"href": "/x"}}})) {
+        Err(HalError::ReservedKey(key)) => assert_eq!(key, "_links"),
+        _ => panic!("_links patch was accepted"),
+    }
+    let ops = vec![PatchOp::Remove { path: "/_embedded/items".to_string() }];
+    assert!(matches!(res.apply_json_patch(&ops), Err(HalError::ReservedKey(_))));
+    assert!(matches!(res.apply_merge_patch(&json!([1])), Err(HalError::InvalidPatch(_))));
+    assert_eq!(res.get_self().unwrap().href, "/orders/12");
+}
+
+#[test]
+fn check_json_patch_follows_rfc6902() {
+    let ops: Vec<PatchOp> = from_value(json!([
+        {"op": "test", "path": "/status", "value": "pending"},
+        {"op": "replace", "path": "/status", "value": "paid"},
+        {"op": "add", "path": "/tags/-", "value": "c"},
+        {"op": "remove", "path": "/tags/0"},
+        {"op": "copy", "from": "/address/city", "path": "/city"},
+        {"op": "move", "from": "/address/zip", "path": "/zip"}
+    ]))
+    .unwrap();
+    let mut res = order();
+    res.apply_json_patch(&ops).unwrap();
+    assert_eq!(
+        res.get_data::<serde_json::Value>().unwrap(),
+        json!({"status": "paid", "tags": ["b", "c"], "address": {"city": "Paris"}, "city": "Paris", "zip": "75001"})
+    );
+}
+
+#[test]
+fn check_failed_json_patch_leaves_resource_untouched() {
+    let ops = vec![
+        PatchOp::Replace { path: "/status".to_string(), value: json!("paid") },
+        PatchOp::Test { path: "/status".to_string(), value: json!("pending") },
+    ];
+    let mut res = order();
+    assert!(matches!(res.apply_json_patch(&ops), Err(HalError::PatchConflict(_))));
+    assert_eq!(res.get_extra_data::<String>("status").unwrap(), "pending");
+}
+
+#[test]
+fn check_diff_round_trips() {
+    let before = order();
+    let mut after = order();
+    after
+        .apply_merge_patch(&json!({"status": "shipped", "address": {"zip": null, "a/b": 1}, "new": true}))
+        .unwrap();
+    let ops = diff(&before, &after);
+    assert!(ops.contains(&PatchOp::Add { path: "/address/a~1b".to_string(), value: json!(1) }));
+    let mut replay = before.clone();
+    replay.apply_json_patch(&ops).unwrap();
+    assert!(replay.structural_eq(&after));
+}
@@ -0,0 +1,129 @@
+use super::super::response::IfNoneMatch;
+use super::super::{HalError, HalLink, HalResource, HalResponse};
+use futures::executor::block_on;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::{get, handler, post, Endpoint, Route};
+use serde_json::json;
+
+#[handler]
+fn order(IfNoneMatch(if_none_match): IfNoneMatch) -> HalResponse {
+    let res = HalResource::new(json!({"id": 12})).with_link("self", "/orders/12");
+    HalResponse::new(res).with_if_none_match(if_none_match)
+}
+
+#[handler]
+fn create(res: HalResource) -> HalResponse {
+    HalResponse::new(res.with_link("self", "/orders/13")).with_status(201)
+}
+
+#[handler]
+fn broken() -> poem::Result<HalResource> {
+    Err(HalError::InvalidPatch("no such path /total".to_string()).into())
+}
+
+#[handler]
+fn cafe() -> HalResponse {
+    let res = HalResource::new(()).with_link("self", HalLink::new("/café").with_title("Café"));
+    HalResponse::new(res).with_link_header(true).with_header("X-Note", "line\nbreak")
+}
+
+fn app() -> impl Endpoint {
+    Route::new()
+        .at("/orders/12", get(order))
+        .at("/orders", post(create))
+        .at("/broken", get(broken))
+        .at("/cafe", get(cafe))
+}
+
+#[test]
+fn check_resources_are_sent_as_hal() {
+    block_on(async {
+        let client = TestClient::new(app());
+        let response = client.get("/orders/12").send().await;
+        response.assert_status_is_ok();
+        response.assert_content_type("application/hal+json; charset=utf-8");
+        let etag = response.0.header("ETag").unwrap().to_string();
+        response
+            .assert_json(json!({"_links": {"self": {"href": "/orders/12"}}, "id": 12}))
+            .await;
+
+        let response = client.get("/orders/12").header("If-None-Match", etag).send().await;
+        response.assert_status(StatusCode::NOT_MODIFIED);
+    });
+}
+
+#[test]
+fn check_hal_bodies_are_read() {
+    block_on(async {
+        let client = TestClient::new(app());
+        let response = client
+            .post("/orders")
+            .content_type("application/hal+json")
+            .body(r#"{"id":13,"_links":{"customer":{"href":"/customers/1"}}}"#)
+            .send()
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        response
+            .assert_json(json!({
+                "_links": {
+                    "customer": {"href": "/customers/1"},
+                    "self": {"href": "/orders/13"}
+                },
+                "id": 13
+            }))
+            .await;
+    });
+}
+
+#[test]
+fn check_errors_are_sent_as_problems() {
+    block_on(async {
+        let client = TestClient::new(app());
+        let response = client
+            .post("/orders")
+            .content_type("text/plain")
+            .body("id=13")
+            .send()
+            .await;
+        response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        response.assert_content_type("application/problem+json");
+        response
+            .assert_json(json!({
+                "detail": "Expected application/hal+json, got text/plain",
+                "status": 415,
+                "title": "Unsupported media type"
+            }))
+            .await;
+
+        let response = client
+            .post("/orders")
+            .content_type("application/json")
+            .body("{")
+            .send()
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        let response = client.get("/broken").send().await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        response.assert_content_type("application/problem+json");
+        response
+            .assert_json(json!({
+                "detail": "Invalid patch: no such path /total",
+                "status": 422,
+                "title": "Invalid patch"
+            }))
+            .await;
+    });
+}
+
+#[test]
+fn check_non_ascii_links_do_not_break_responses() {
+    block_on(async {
+        let client = TestClient::new(app());
+        let response = client.get("/cafe").send().await;
+        response.assert_status_is_ok();
+        response.assert_header("Link", "</caf%C3%A9>; rel=\"self\"; title*=UTF-8''Caf%C3%A9");
+        response.assert_header_is_not_exist("X-Note");
+    });
+}
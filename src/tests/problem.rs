@@ -0,0 +1,72 @@
+use super::super::problem::{Problem, VndError, PROBLEM_CONTENT_TYPE, VND_ERROR_CONTENT_TYPE};
+use super::super::{HalError, HalResponse};
+use serde_json::{from_str, to_value, Value as JsonValue};
+
+/// Parse `json`, to compare documents regardless of key order.
+fn parse(json: &str) -> JsonValue {
+    from_str(json).unwrap()
+}
+
+#[test]
+fn check_vnd_error_gets_serialized_with_nested_errors() {
+    let error = VndError::new("Validation failed")
+        .with_status(400)
+        .with_logref(42)
+        .with_help("https://docs.com/validation")
+        .with_error(VndError::new("Username missing").with_path("/username"))
+        .with_error(VndError::new("Email invalid").with_path("/email"));
+    assert_eq!(
+        to_value(error.to_resource()).unwrap(),
+        parse(r#"{"_links":{"help":{"href":"https://docs.com/validation"}},"_embedded":{"errors":[{"message":"Username missing","path":"/username"},{"message":"Email invalid","path":"/email"}]},"logref":42,"message":"Validation failed","total":2}"#)
+    );
+    let response = error.to_response();
+    assert_eq!(response.status(), 400);
+    assert_eq!(response.content_type(), VND_ERROR_CONTENT_TYPE);
+}
+
+#[test]
+fn check_problem_gets_serialized_with_extensions() {
+    let problem = Problem::new(403)
+        .with_type("https://docs.com/out-of-credit")
+        .with_title("Out of credit")
+        .with_instance("/accounts/12/msgs/abc")
+        .with_extension("balance", 30)
+        .with_describes("/accounts/12");
+    assert_eq!(
+        to_value(problem.to_resource()).unwrap(),
+        parse(r#"{"_links":{"describes":{"href":"/accounts/12"}},"balance":30,"instance":"/accounts/12/msgs/abc","status":403,"title":"Out of credit","type":"https://docs.com/out-of-credit"}"#)
+    );
+}
+
+#[test]
+fn check_hal_error_converts_to_problem_response() {
+    let response = HalResponse::from(HalError::Custom("boom".to_string()));
+    assert_eq!(response.status(), 500);
+    assert_eq!(response.content_type(), PROBLEM_CONTENT_TYPE);
+    assert_eq!(
+        parse(&response.body().unwrap()),
+        parse(r#"{"status":500,"title":"Internal Hal Error"}"#)
+    );
+
+    let json = from_str::<JsonValue>("{").unwrap_err();
+    let problem = Problem::from(HalError::from(json)).to_resource();
+    assert_eq!(
+        to_value(problem).unwrap(),
+        parse(r#"{"status":500,"title":"Error in json processing"}"#)
+    );
+}
+
+#[test]
+fn check_client_errors_keep_their_detail() {
+    let response = HalResponse::from(HalError::PatchConflict("/total is 30".to_string()));
+    assert_eq!(response.status(), 409);
+    assert_eq!(
+        parse(&response.body().unwrap()),
+        parse(r#"{"detail":"Patch conflict: /total is 30","status":409,"title":"Patch conflict"}"#)
+    );
+    let problem = Problem::from(HalError::ReservedKey("_links".to_string()));
+    assert_eq!(
+        problem.to_resource().get_extra_data::<String>("detail").unwrap(),
+        "Reserved key _links in payload"
+    );
+}
@@ -0,0 +1,71 @@
+use super::super::rel::{self, Rel};
+use super::super::{HalError, HalResource};
+use serde_json::{json, to_value};
+
+#[test]
+fn check_registered_rels_are_accepted() {
+    assert!(Rel::NEXT.is_registered());
+    assert!(rel::is_registered("Describedby"));
+    assert!(rel::check("self").is_ok());
+    assert!(rel::check("curies").is_ok());
+}
+
+#[test]
+fn check_custom_rels_are_accepted() {
+    assert!(rel::check("acme:widgets").is_ok());
+    assert!(rel::check("https://rels.acme.com/widgets").is_ok());
+    assert!(rel::check("items").is_ok());
+    assert!(rel::check("orders").is_ok());
+    assert!(Rel::curie("acme", "widgets").is_extension());
+}
+
+#[test]
+fn check_typos_get_flagged() {
+    assert_eq!(rel::suggest("colection"), Some("collection"));
+    assert_eq!(rel::suggest("licnese"), Some("license"));
+    assert_eq!(rel::suggest("stylesheat"), Some("stylesheet"));
+    assert!(matches!(rel::check("Previuos"), Err(HalError::SuspiciousRel(_, _))));
+}
+
+#[test]
+fn check_domain_rels_are_not_typos() {
+    for name in ["owner", "parent", "comments", "posts", "reviews", "archive", "content"] {
+        assert_eq!(rel::suggest(name), None, "{} was flagged", name);
+    }
+    assert_eq!(rel::suggest("nxt"), None);
+    assert_eq!(rel::suggest("slef"), None);
+    assert_eq!(rel::suggest("colllection"), Some("collection"));
+    assert_eq!(rel::suggest("collectino"), Some("collection"));
+    assert_eq!(rel::suggest("cllectoin"), None);
+}
+
+#[test]
+fn check_rel_can_be_used_as_link_name() {
+    let res = HalResource::new(())
+        .with_link(Rel::SELF, "https://self.com")
+        .with_link(Rel::custom("orders"), "https://self.com/orders");
+    assert_eq!(
+        to_value(&res).unwrap(),
+        json!({"_links": {
+            "orders": {"href": "https://self.com/orders"},
+            "self": {"href": "https://self.com"},
+        }})
+    );
+    assert!(res.get_link(Rel::SELF).is_some());
+    assert!(res.get_links(Rel::custom("orders")).is_some());
+}
+
+#[test]
+fn check_resource_validation_flags_typos_in_embedded() {
+    let child = HalResource::new(()).with_link("canonicl", "https://self.com/1");
+    let res = HalResource::new(())
+        .with_link("next", "https://self.com/2")
+        .with_resource("item", child);
+    match res.check_rels() {
+        Err(HalError::SuspiciousRel(name, reg)) => {
+            assert_eq!(name, "canonicl");
+            assert_eq!(reg, "canonical");
+        }
+        _ => panic!("typo was not flagged"),
+    }
+}
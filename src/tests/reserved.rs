@@ -0,0 +1,61 @@
+use super::super::reserved::KeyPolicy;
+use super::super::{HalError, HalResource};
+use serde_json::{from_str, json, to_string, to_value, Value};
+
+#[test]
+fn check_reserved_keys_get_rejected() {
+    match HalResource::try_new(json!({"a": 1, "_embedded": {}}), &KeyPolicy::default()) {
+        Err(HalError::ReservedKey(key)) => assert_eq!(key, "_embedded"),
+        _ => panic!("_embedded was accepted"),
+    }
+    let res = HalResource::new(json!({"a": 1}));
+    assert!(matches!(
+        res.try_with_extra_data("_curies", 1, &KeyPolicy::reject()),
+        Err(HalError::ReservedKey(_))
+    ));
+}
+
+#[test]
+fn check_infallible_constructors_leave_reserved_keys_out() {
+    let payload = json!({"a": 1, "_id": 7, "_links": "mine", "_embedded": {}, "_curies": []});
+    let res = HalResource::new(&payload)
+        .with_extra_data("_links", 1)
+        .with_extra_data("b", 2);
+    assert_eq!(to_value(&res).unwrap(), json!({"_id": 7, "a": 1, "b": 2}));
+}
+
+#[test]
+fn check_underscored_keys_are_kept_unless_asked() {
+    let payload = json!({"_id": 12, "_links": "x"});
+    let res = HalResource::try_new(&payload, &KeyPolicy::drop()).unwrap();
+    assert_eq!(to_string(&res).unwrap(), r#"{"_id":12}"#);
+
+    let res = HalResource::try_new(&payload, &KeyPolicy::drop().with_underscored(true)).unwrap();
+    assert_eq!(to_string(&res).unwrap(), "{}");
+}
+
+#[test]
+fn check_renaming_never_clobbers_a_key() {
+    let policy = KeyPolicy::rename("hal");
+    let res = HalResource::new(json!({"a": 1}))
+        .try_with_extra_data("_links", "mine", &policy)
+        .unwrap();
+    assert_eq!(res.get_extra_data::<String>("hal_links").unwrap(), "mine");
+
+    let payload = json!({"_links": 1, "hal_links": 2});
+    assert!(matches!(
+        HalResource::try_new(&payload, &policy),
+        Err(HalError::ReservedKey(_))
+    ));
+}
+
+#[test]
+fn check_reserved_keys_never_leak_into_payload() {
+    let res: HalResource =
+        from_str(r#"{"_links":{"self":{"href":"/a"}},"_curies":{},"_id":3,"a":1}"#).unwrap();
+    assert_eq!(res.get_data::<Value>().unwrap(), json!({"_id": 3, "a": 1}));
+    assert_eq!(
+        to_string(&res).unwrap(),
+        r#"{"_links":{"self":{"href":"/a"}},"_id":3,"a":1}"#
+    );
+}
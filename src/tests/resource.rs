@@ -2,7 +2,7 @@ use super::{
     super::{resource::*, HalLink},
     Test1,
 };
-use serde_json::{from_str, to_string};
+use serde_json::{from_str, to_string, Value as JsonValue};
 
 //#[derive(Serialize, Deserialize)]
 // struct Test1 {
@@ -39,7 +39,10 @@ fn check_link_arrays_get_serialized() {
       .with_link("alfa", "https://self.com/gamma");
 
     let s = to_string(&f).unwrap();
-    assert_eq!(s, r#"{"_links":{"alfa":[{"href":"https://self.com/beta"},{"href":"https://self.com/gamma"}],"self":{"href":"https://self.com"}},"a":"Test"}"#);
+    assert_eq!(
+        from_str::<JsonValue>(&s).unwrap(),
+        from_str::<JsonValue>(r#"{"_links":{"alfa":[{"href":"https://self.com/beta"},{"href":"https://self.com/gamma"}],"self":{"href":"https://self.com"}},"a":"Test"}"#).unwrap()
+    );
 }
 
 #[test]
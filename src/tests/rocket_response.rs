@@ -0,0 +1,108 @@
+use super::super::response::problem_catchers;
+use super::super::{HalError, HalResource, HalResponse, HalResult};
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::{from_str, json, Value as JsonValue};
+
+#[rocket::get("/orders/12")]
+fn order() -> HalResource {
+    HalResource::new(json!({"id": 12})).with_link("self", "/orders/12")
+}
+
+#[rocket::post("/orders", data = "<res>")]
+fn create(res: HalResource) -> HalResponse {
+    HalResponse::new(res.with_link("self", "/orders/13")).with_status(201)
+}
+
+#[rocket::get("/broken")]
+fn broken() -> HalResult<HalResource> {
+    Err(HalError::InvalidPatch("no such path /total".to_string()))
+}
+
+fn client() -> Client {
+    let rocket = rocket::build()
+        .mount("/", rocket::routes![order, create, broken])
+        .register("/", problem_catchers());
+    Client::untracked(rocket).unwrap()
+}
+
+#[test]
+fn check_resources_are_sent_as_hal() {
+    let client = client();
+    let response = client.get("/orders/12").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get_one("Content-Type"),
+        Some("application/hal+json; charset=utf-8")
+    );
+    let etag = response.headers().get_one("ETag").unwrap().to_string();
+    assert_eq!(
+        from_str::<JsonValue>(&response.into_string().unwrap()).unwrap(),
+        json!({"_links": {"self": {"href": "/orders/12"}}, "id": 12})
+    );
+
+    let response = client
+        .get("/orders/12")
+        .header(Header::new("If-None-Match", etag))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotModified);
+}
+
+#[test]
+fn check_hal_bodies_are_read() {
+    let client = client();
+    let response = client
+        .post("/orders")
+        .header(ContentType::new("application", "hal+json"))
+        .body(r#"{"id":13,"_links":{"customer":{"href":"/customers/1"}}}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        from_str::<JsonValue>(&response.into_string().unwrap()).unwrap(),
+        json!({
+            "_links": {
+                "customer": {"href": "/customers/1"},
+                "self": {"href": "/orders/13"}
+            },
+            "id": 13
+        })
+    );
+}
+
+#[test]
+fn check_errors_are_sent_as_problems() {
+    let client = client();
+    let response = client
+        .post("/orders")
+        .header(ContentType::Plain)
+        .body("id=13")
+        .dispatch();
+    assert_eq!(response.status(), Status::UnsupportedMediaType);
+    assert_eq!(response.headers().get_one("Content-Type"), Some("application/problem+json"));
+    assert_eq!(
+        from_str::<JsonValue>(&response.into_string().unwrap()).unwrap(),
+        json!({
+            "detail": "Expected application/hal+json, got text/plain",
+            "status": 415,
+            "title": "Unsupported media type"
+        })
+    );
+
+    let response = client.post("/orders").header(ContentType::JSON).body("{").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    assert!(response.into_string().unwrap().contains(r#""title":"Malformed HAL document""#));
+
+    let response = client.get("/broken").dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        from_str::<JsonValue>(&response.into_string().unwrap()).unwrap(),
+        json!({"detail": "Invalid patch: no such path /total", "status": 422, "title": "Invalid patch"})
+    );
+
+    let response = client.get("/nowhere").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(
+        from_str::<JsonValue>(&response.into_string().unwrap()).unwrap(),
+        json!({"status": 404, "title": "Not Found"})
+    );
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "actix-web")]
+use super::super::route::actix;
+use super::super::route::{expand, link_to, HalRoute, RoutePrefix};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize)]
+struct ItemPath {
+    order: u64,
+    item: Option<String>,
+}
+
+struct ItemRoute;
+
+impl HalRoute for ItemRoute {
+    const PATH: &'static str = "/orders/{order}/items/{item}";
+    type Params = ItemPath;
+}
+
+#[test]
+fn check_route_links_get_expanded() {
+    let link = link_to::<ItemRoute>(ItemPath {
+        order: 12,
+        item: Some("a/b c".to_string()),
+    });
+    assert_eq!(link.href, "/orders/12/items/a%2Fb%20c");
+    assert!(!link.templated);
+}
+
+#[test]
+fn check_unbound_parameters_make_templates() {
+    let link = link_to::<ItemRoute>(ItemPath { order: 12, item: None });
+    assert_eq!(link.href, "/orders/12/items/{item}");
+    assert!(link.templated);
+
+    let link = expand("/files/{*path}", &json!({}));
+    assert_eq!(link.href, "/files/{+path}");
+    assert!(link.templated);
+    let link = expand("/files/{*path}", &json!({"path": "docs/a b.txt"}));
+    assert_eq!(link.href, "/files/docs/a%20b.txt");
+}
+
+#[test]
+fn check_links_respect_mount_prefix() {
+    let prefix = RoutePrefix::from_paths("/api/v1/orders/12", "/orders/12");
+    assert_eq!(prefix, RoutePrefix("/api/v1".to_string()));
+    assert_eq!(RoutePrefix::from_paths("/api/", "/").0, "/api");
+    assert_eq!(RoutePrefix::from_paths("/api", "/").0, "/api");
+    assert_eq!(RoutePrefix::from_paths("/orders", "/orders").0, "");
+    let link = prefix.link_to::<ItemRoute>(ItemPath { order: 1, item: None });
+    assert_eq!(link.href, "/api/v1/orders/1/items/{item}");
+}
+
+#[cfg(feature = "actix-web")]
+#[test]
+fn check_placeholders_stay_letters() {
+    assert_eq!(actix::placeholder(0, 3), "halsegmenta");
+    assert_eq!(actix::placeholder(25, 26), "halsegmentz");
+    assert_eq!(actix::placeholder(0, 27), "halsegmentaa");
+    assert_eq!(actix::placeholder(26, 27), "halsegmentba");
+    assert_eq!(actix::placeholder(300, 301), "halsegmentlo");
+    let placeholders: Vec<String> = (0..700).map(|index| actix::placeholder(index, 700)).collect();
+    assert!(placeholders.iter().all(|p| p.bytes().all(|b| b.is_ascii_lowercase())));
+    assert!(placeholders.iter().all(|p| p.len() == "halsegmentaaa".len()));
+    let unique: std::collections::HashSet<_> = placeholders.iter().collect();
+    assert_eq!(unique.len(), 700);
+}
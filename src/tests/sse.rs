@@ -0,0 +1,116 @@
+#[cfg(any(feature = "axumweb", feature = "actix"))]
+use super::super::sse::HalSse;
+use super::super::sse::{HalEvent, HalEventReader};
+use super::super::HalResource;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Order {
+    id: u32,
+    state: String,
+}
+
+fn orders() -> Vec<HalResource> {
+    vec![
+        HalResource::new(json!({"id": 12, "state": "paid"})).with_link("self", "/orders/12"),
+        HalResource::new(json!({"id": 13, "state": "shipped"})).with_link("self", "/orders/13"),
+    ]
+}
+
+#[test]
+fn check_events_from_resources() {
+    let event = HalEvent::from_resource(&orders()[0]);
+    assert_eq!(event.event.as_deref(), Some("paid"));
+    assert_eq!(event.id.as_deref(), Some("/orders/12"));
+
+    let anonymous = HalResource::new(json!({"id": 14}));
+    let event = HalEvent::from_resource(&anonymous);
+    assert_eq!(event.event, None);
+    assert_eq!(event.id, Some(anonymous.etag().unwrap()));
+
+    let event = event.with_event("bad\nname").with_id("line\rbreak");
+    assert_eq!(event.event, None);
+    assert_eq!(event.id, Some(anonymous.etag().unwrap()));
+
+    let event = HalEvent {
+        data: "a\r\nb".to_string(),
+        ..HalEvent::default()
+    };
+    assert_eq!(event.encode(), "data: a\ndata: b\n\n");
+}
+
+#[test]
+fn check_reader_gives_back_typed_resources() {
+    let stream: String = orders().iter().map(|o| HalEvent::from_resource(o).encode()).collect();
+    let mut reader = HalEventReader::new();
+    let mut events = Vec::new();
+    for chunk in stream.as_bytes().chunks(7) {
+        events.extend(reader.feed(chunk));
+    }
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].event.as_deref(), Some("shipped"));
+    assert_eq!(
+        events[1].get_data::<Order>().unwrap(),
+        Order {
+            id: 13,
+            state: "shipped".to_string()
+        }
+    );
+    assert!(events[1].resource().unwrap().structural_eq(&orders()[1]));
+    assert_eq!(reader.last_event_id(), Some("/orders/13"));
+}
+
+#[test]
+fn check_reader_follows_the_stream_format() {
+    let mut reader = HalEventReader::new();
+    let stream = concat!(
+        "\u{feff}: keep-alive\r\nretry: 3000\r\nid: 7\r\n",
+        "data: {\"name\":\r\ndata:\"caf\u{e9}\"}\r\n\r\n",
+        "data: 1\r\rdata: 2\n",
+    );
+    let bytes = stream.as_bytes();
+    let split = bytes.iter().position(|&b| b == 0xc3).unwrap() + 1;
+    let mut events = reader.feed(&bytes[..split]);
+    events.extend(reader.feed(&bytes[split..]));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event, None);
+    assert_eq!(events[0].id.as_deref(), Some("7"));
+    assert_eq!(events[0].data, "{\"name\":\n\"caf\u{e9}\"}");
+    assert_eq!(events[1].id.as_deref(), Some("7"));
+    assert_eq!(events[1].data, "1");
+    assert_eq!(reader.retry(), Some(Duration::from_millis(3000)));
+    assert_eq!(reader.feed(b"\n")[0].data, "2");
+}
+
+#[cfg(feature = "axumweb")]
+#[test]
+fn check_axum_streams_events() {
+    use axum::response::IntoResponse;
+
+    let response = HalSse::new(futures::stream::iter(orders())).into_response();
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX);
+    let body = futures::executor::block_on(body).unwrap();
+    let events = HalEventReader::new().feed(&body);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0], HalEvent::from_resource(&orders()[0]));
+}
+
+#[cfg(feature = "actix")]
+#[actix_web::test]
+async fn check_actix_streams_events() {
+    use actix_web::{test, web, App};
+
+    let stream = || async { HalSse::new(futures::stream::iter(orders())) };
+    let app = test::init_service(App::new().route("/orders", web::get().to(stream))).await;
+    let req = test::TestRequest::get().uri("/orders").to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/event-stream");
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-cache");
+    let body = test::read_body(response).await;
+    let events = HalEventReader::new().feed(&body);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1], HalEvent::from_resource(&orders()[1]));
+}
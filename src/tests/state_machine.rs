@@ -0,0 +1,93 @@
+use super::super::state_machine::HalStateMachine;
+use super::super::{HalError, HalResource};
+use serde_json::{json, to_value};
+
+fn orders() -> HalStateMachine {
+    HalStateMachine::new("Order")
+        .with_transition("pending", "paid", "pay", "post", "/orders/{id}/payment")
+        .with_transition_titled(
+            "pending",
+            "cancelled",
+            "cancel",
+            "DELETE",
+            "/orders/{id}",
+            Some("Cancel"),
+        )
+        .with_transition("paid", "shipped", "ship", "POST", "/orders/{id}/shipment{?carrier}")
+        .with_state("archived")
+}
+
+#[test]
+fn check_only_allowed_transitions_get_linked() {
+    let order = orders().apply(HalResource::new(json!({"id": "a b"})), "pending");
+    assert_eq!(
+        to_value(&order).unwrap(),
+        json!({
+            "_links": {
+                "cancel": {"href": "/orders/a%20b", "title": "Cancel", "method": "DELETE"},
+                "pay": {"href": "/orders/a%20b/payment", "method": "POST"}
+            },
+            "id": "a b"
+        })
+    );
+
+    let order = orders().apply(HalResource::new(json!({"id": 12})), "paid");
+    let ship = order.get_link("ship").unwrap();
+    assert_eq!(ship.href, "/orders/12/shipment{?carrier}");
+    assert!(ship.templated);
+    assert!(order.get_link("pay").is_none());
+
+    let order = orders().apply(HalResource::new(json!({"id": 12})), "shipped");
+    assert_eq!(to_value(&order).unwrap(), json!({"id": 12}));
+}
+
+#[test]
+fn check_transitions_get_followed() {
+    let machine = orders();
+    assert_eq!(machine.next_state("pending", "cancel").unwrap(), "cancelled");
+    assert!(matches!(machine.next_state("shipped", "pay"), Err(HalError::Custom(_))));
+    assert_eq!(machine.states(), ["pending", "paid", "cancelled", "shipped", "archived"]);
+    assert_eq!(machine.transitions_from("pending").count(), 2);
+}
+
+#[test]
+fn check_machine_gets_exported_as_diagrams() {
+    let machine = HalStateMachine::new("Order")
+        .with_transition("pending", "paid", "pay", "POST", "/orders/{id}/payment");
+    assert_eq!(
+        machine.to_mermaid(),
+        "stateDiagram-v2\n    pending\n    paid\n    pending --> paid : pay (POST)\n"
+    );
+    assert_eq!(
+        machine.to_dot(),
+        "digraph \"Order\" {\n    \"pending\";\n    \"paid\";\n    \"pending\" -> \"paid\" [label=\"pay (POST /orders/{id}/payment)\"];\n}\n"
+    );
+}
+
+#[test]
+fn check_diagram_names_get_escaped() {
+    let machine = HalStateMachine::new("Order \"v2\"")
+        .with_transition("on hold", "paid", "pay [now]", "POST", "/orders/{id}")
+        .with_state("_0");
+    assert_eq!(
+        machine.to_mermaid(),
+        concat!(
+            "stateDiagram-v2\n",
+            "    state \"on hold\" as _0\n",
+            "    paid\n",
+            "    state \"_0\" as _2\n",
+            "    _0 --> paid : pay #91;now#93; (POST)\n",
+        )
+    );
+    assert_eq!(
+        machine.to_dot(),
+        concat!(
+            "digraph \"Order \\\"v2\\\"\" {\n",
+            "    \"on hold\";\n",
+            "    \"paid\";\n",
+            "    \"_0\";\n",
+            "    \"on hold\" -> \"paid\" [label=\"pay [now] (POST /orders/{id})\"];\n",
+            "}\n",
+        )
+    );
+}
@@ -0,0 +1,105 @@
+use super::super::uri::{expand, expand_with, query_pairs, relativize, resolve};
+use super::super::{HalLink, HalResource};
+use serde_json::json;
+
+const BASE: &str = "http://a/b/c/d;p?q";
+
+#[test]
+fn check_rfc3986_normal_examples() {
+    assert_eq!(resolve(BASE, "g:h"), "g:h");
+    assert_eq!(resolve(BASE, "g"), "http://a/b/c/g");
+    assert_eq!(resolve(BASE, "./g"), "http://a/b/c/g");
+    assert_eq!(resolve(BASE, "g/"), "http://a/b/c/g/");
+    assert_eq!(resolve(BASE, "/g"), "http://a/g");
+    assert_eq!(resolve(BASE, "//g"), "http://g");
+    assert_eq!(resolve(BASE, "?y"), "http://a/b/c/d;p?y");
+    assert_eq!(resolve(BASE, "g?y"), "http://a/b/c/g?y");
+    assert_eq!(resolve(BASE, "#s"), "http://a/b/c/d;p?q#s");
+    assert_eq!(resolve(BASE, ""), "http://a/b/c/d;p?q");
+    assert_eq!(resolve(BASE, "."), "http://a/b/c/");
+    assert_eq!(resolve(BASE, ".."), "http://a/b/");
+    assert_eq!(resolve(BASE, "../g"), "http://a/b/g");
+    assert_eq!(resolve(BASE, "../../g"), "http://a/g");
+}
+
+#[test]
+fn check_rfc3986_abnormal_examples() {
+    assert_eq!(resolve(BASE, "../../../g"), "http://a/g");
+    assert_eq!(resolve(BASE, "/./g"), "http://a/g");
+    assert_eq!(resolve(BASE, "g."), "http://a/b/c/g.");
+    assert_eq!(resolve(BASE, "./g/."), "http://a/b/c/g/");
+    assert_eq!(resolve(BASE, "g/../h"), "http://a/b/c/h");
+    assert_eq!(resolve(BASE, "g;x=1/../y"), "http://a/b/c/y");
+}
+
+#[test]
+fn check_templates_survive_resolution() {
+    assert_eq!(
+        resolve("http://a/orders/", "search{?q,page}"),
+        "http://a/orders/search{?q,page}"
+    );
+    assert_eq!(resolve("http://a/orders/", "{+base}/x"), "{+base}/x");
+}
+
+#[test]
+fn check_relativize() {
+    let base = "https://api.com/v1/orders";
+    assert_eq!(relativize(base, "https://api.com/v1/12"), "12");
+    assert_eq!(relativize(base, "https://api.com/v2/12?x"), "/v2/12?x");
+    assert_eq!(relativize(base, "https://other.com/v1/12"), "https://other.com/v1/12");
+    assert_eq!(relativize(base, "https://api.com/v1/a:b"), "./a:b");
+    assert_eq!(resolve(base, &relativize(base, "https://api.com/v1/a:b")), "https://api.com/v1/a:b");
+}
+
+#[test]
+fn check_resource_gets_resolved_recursively() {
+    let child = HalResource::new(()).with_link("self", "items/1");
+    let res = HalResource::new(())
+        .with_link("self", "/orders/12")
+        .with_link("next", HalLink::new("http://other.com/x"))
+        .with_curie("acme", "/docs/{rel}")
+        .with_resource("item", child)
+        .resolve_against("https://api.com/orders/12");
+
+    assert_eq!(res.get_self().unwrap().href, "https://api.com/orders/12");
+    assert_eq!(res.get_link("next").unwrap().href, "http://other.com/x");
+    assert_eq!(res.get_link("curies").unwrap().href, "https://api.com/docs/{rel}");
+
+    let back = res.relativize("https://api.com/orders/");
+    assert_eq!(back.get_self().unwrap().href, "12");
+    assert_eq!(back.get_link("curies").unwrap().href, "/docs/{rel}");
+}
+
+#[test]
+fn check_query_pairs_get_decoded() {
+    assert_eq!(
+        query_pairs("q=caf%C3%A9+au+lait&bad=%+f%2&flag"),
+        vec![
+            ("q".to_string(), "café au lait".to_string()),
+            ("bad".to_string(), "% f%2".to_string()),
+            ("flag".to_string(), String::new()),
+        ]
+    );
+}
+
+#[test]
+fn check_templates_get_expanded() {
+    let values = json!({"id": 12, "path": "/docs/a b", "q": null});
+    assert_eq!(expand("/orders/{id}", &values), ("/orders/12".to_string(), false));
+    assert_eq!(expand("/files/{*path}", &values), ("/files/docs/a%20b".to_string(), false));
+    assert_eq!(expand("/files{+path}", &values), ("/files/docs/a%20b".to_string(), false));
+    assert_eq!(
+        expand("/orders/{id}/{q}{?page}", &values),
+        ("/orders/12/{q}{?page}".to_string(), true)
+    );
+    assert_eq!(expand("/files/{*rest}", &values), ("/files/{+rest}".to_string(), true));
+    assert_eq!(expand("/orders/{id", &values), ("/orders/{id".to_string(), false));
+
+    let mut names = Vec::new();
+    let (href, templated) = expand_with("/{a}/{b:x}/{}", |name| {
+        names.push(name.to_string());
+        None
+    });
+    assert_eq!((href.as_str(), templated), ("/{a}/{b:x}/{}", true));
+    assert_eq!(names, ["a"]);
+}
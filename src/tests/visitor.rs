@@ -0,0 +1,52 @@
+use super::super::visitor::{HalVisitor, PrefixRewriter};
+use super::super::{HalLink, HalResource};
+
+struct RelCollector(Vec<String>);
+
+impl HalVisitor for RelCollector {
+    fn visit_link(&mut self, rel: &str, _link: &HalLink) {
+        self.0.push(rel.to_string());
+    }
+}
+
+fn resource() -> HalResource {
+    let child = HalResource::new(())
+        .with_link("self", "http://orders.internal/12")
+        .with_link("customer", "http://customers.internal/3");
+    HalResource::new(())
+        .with_link("self", "http://orders.internal")
+        .with_link("search", HalLink::new("http://orders.internal{?q}").templated(true))
+        .with_link("legacy", "http://orders.internal.old/1")
+        .with_resource("item", child)
+}
+
+#[test]
+fn check_visitor_walks_links_recursively() {
+    let mut collector = RelCollector(Vec::new());
+    resource().accept(&mut collector);
+    collector.0.sort();
+    assert_eq!(collector.0, vec!["customer", "legacy", "search", "self", "self"]);
+}
+
+#[test]
+fn check_rewrite_hrefs_reaches_embedded_resources() {
+    let res = resource().rewrite_hrefs(|href| href.to_uppercase());
+    let items = res.get_resources("item").unwrap();
+    assert_eq!(items[0].get_self().unwrap().href, "HTTP://ORDERS.INTERNAL/12");
+}
+
+#[test]
+fn check_prefix_rewriter_respects_boundaries_and_templates() {
+    let mut gateway = PrefixRewriter::new()
+        .with_prefix("http://orders.internal", "https://api.com/orders/")
+        .with_prefix("http://customers.internal", "https://api.com/customers");
+    let mut res = resource();
+    res.accept_mut(&mut gateway);
+
+    assert_eq!(res.get_self().unwrap().href, "https://api.com/orders");
+    assert_eq!(res.get_link("search").unwrap().href, "https://api.com/orders{?q}");
+    assert_eq!(res.get_link("legacy").unwrap().href, "http://orders.internal.old/1");
+    let items = res.get_resources("item").unwrap();
+    assert_eq!(items[0].get_self().unwrap().href, "https://api.com/orders/12");
+    assert_eq!(items[0].get_link("customer").unwrap().href, "https://api.com/customers/3");
+}
@@ -0,0 +1,315 @@
+//! URI reference resolution, following [RFC 3986](https://tools.ietf.org/html/rfc3986#section-5).
+//!
+//! The functions work on plain strings and never re-encode their input, so
+//! URI Templates (RFC 6570) survive resolution as long as they do not start
+//! with an expression.
+//!
+//! `expand` and `expand_with` fill the simple variables of such templates,
+//! and are shared by the modules building links from them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::uri::{relativize, resolve};
+//!
+//! let base = "http://api.com/orders/";
+//! assert_eq!(resolve(base, "12"), "http://api.com/orders/12");
+//! assert_eq!(resolve(base, "../customers/1"), "http://api.com/customers/1");
+//! assert_eq!(relativize(base, "http://api.com/orders/12"), "12");
+//! assert_eq!(relativize(base, "http://api.com/customers/1"), "/customers/1");
+//! ```
+use serde_json::Value as JsonValue;
+
+/// The five components of a URI reference.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Parts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+fn parse(uri: &str) -> Parts<'_> {
+    let mut rest = uri;
+    let mut parts = Parts::default();
+
+    if let Some(i) = rest.find('#') {
+        parts.fragment = Some(&rest[i + 1..]);
+        rest = &rest[..i];
+    }
+    if let Some(i) = rest.find('?') {
+        parts.query = Some(&rest[i + 1..]);
+        rest = &rest[..i];
+    }
+    if let Some(i) = rest.find(':') {
+        let scheme = &rest[..i];
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+        if valid {
+            parts.scheme = Some(scheme);
+            rest = &rest[i + 1..];
+        }
+    }
+    if let Some(after) = rest.strip_prefix("//") {
+        let end = after.find('/').unwrap_or(after.len());
+        parts.authority = Some(&after[..end]);
+        rest = &after[end..];
+    }
+    parts.path = rest;
+    parts
+}
+
+fn recompose(parts: &Parts) -> String {
+    let mut out = String::new();
+    if let Some(scheme) = parts.scheme {
+        out.push_str(scheme);
+        out.push(':');
+    }
+    if let Some(authority) = parts.authority {
+        out.push_str("//");
+        out.push_str(authority);
+    }
+    out.push_str(parts.path);
+    if let Some(query) = parts.query {
+        out.push('?');
+        out.push_str(query);
+    }
+    if let Some(fragment) = parts.fragment {
+        out.push('#');
+        out.push_str(fragment);
+    }
+    out
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output: Vec<&str> = Vec::new();
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix("../") {
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix("./") {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input == "/.." { "/" } else { &input[3..] };
+            output.pop();
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = if input.starts_with('/') { 1 } else { 0 };
+            let end = input[start..].find('/').map_or(input.len(), |i| i + start);
+            output.push(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output.concat()
+}
+
+fn merge(base: &Parts, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        format!("/{}", path)
+    } else {
+        match base.path.rfind('/') {
+            Some(i) => format!("{}{}", &base.path[..=i], path),
+            None => path.to_string(),
+        }
+    }
+}
+
+/// Whether `uri` is absolute, i.e. has a scheme.
+pub fn is_absolute(uri: &str) -> bool {
+    parse(uri).scheme.is_some()
+}
+
+/// Resolve `reference` against `base`, as in RFC 3986 section 5.2.
+///
+/// References starting with a URI Template expression (`{`) are returned
+/// unchanged, since the expression may itself expand to an absolute URI.
+pub fn resolve(base: &str, reference: &str) -> String {
+    if reference.starts_with('{') {
+        return reference.to_string();
+    }
+    let b = parse(base);
+    let r = parse(reference);
+    let path;
+    let target = if r.scheme.is_some() {
+        path = remove_dot_segments(r.path);
+        Parts { path: &path, ..r }
+    } else if r.authority.is_some() {
+        path = remove_dot_segments(r.path);
+        Parts { scheme: b.scheme, path: &path, ..r }
+    } else if r.path.is_empty() {
+        Parts {
+            scheme: b.scheme,
+            authority: b.authority,
+            path: b.path,
+            query: r.query.or(b.query),
+            fragment: r.fragment,
+        }
+    } else {
+        path = if r.path.starts_with('/') {
+            remove_dot_segments(r.path)
+        } else {
+            remove_dot_segments(&merge(&b, r.path))
+        };
+        Parts {
+            scheme: b.scheme,
+            authority: b.authority,
+            path: &path,
+            ..r
+        }
+    };
+    recompose(&target)
+}
+
+/// The shortest reference to `target` that resolves back to it against `base`.
+///
+/// Targets on another scheme or authority are returned unchanged. Targets
+/// below the base's directory become relative paths; other targets on the
+/// same authority become absolute paths.
+pub fn relativize(base: &str, target: &str) -> String {
+    let b = parse(base);
+    let t = parse(target);
+    if t.scheme.is_none() || t.scheme != b.scheme || t.authority != b.authority {
+        return target.to_string();
+    }
+    let dir = match b.path.rfind('/') {
+        Some(i) => &b.path[..=i],
+        None => "",
+    };
+    let path = match t.path.strip_prefix(dir) {
+        Some(rest) if !dir.is_empty() && !rest.is_empty() && !rest.starts_with('/') => {
+            let first = rest.split('/').next().unwrap_or("");
+            if first.contains(':') {
+                format!("./{}", rest)
+            } else {
+                rest.to_string()
+            }
+        }
+        _ => t.path.to_string(),
+    };
+    recompose(&Parts {
+        scheme: None,
+        authority: None,
+        path: &path,
+        ..t
+    })
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                out.push(u8::from_str_radix(hex, 16).unwrap_or(b'%'));
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986.
+pub fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            b => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Split a query string (`a=1&b=2`, without the `?`) into decoded
+/// name/value pairs, in order.
+pub fn query_pairs(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.find('=') {
+            Some(i) => (percent_decode(&pair[..i]), percent_decode(&pair[i + 1..])),
+            None => (percent_decode(pair), String::new()),
+        })
+        .collect()
+}
+
+/// Expand the variables of the URI Template `template` with the values
+/// `lookup` gives for their names, and tell whether expressions are left.
+///
+/// `{name}` gets its value percent-encoded. `{+name}`, and `{*name}` as
+/// axum writes a catch-all, get theirs encoded segment by segment, keeping
+/// the slashes. Variables without a value stay in the template, `{*name}`
+/// becoming `{+name}`, and so do the expressions of the other operators.
+pub fn expand_with<F>(template: &str, mut lookup: F) -> (String, bool)
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut out = String::with_capacity(template.len());
+    let mut templated = false;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        out.push_str(&rest[..start]);
+        let expression = &rest[start + 1..end];
+        let (name, segments) = match expression.as_bytes().first() {
+            Some(b'*') | Some(b'+') => (&expression[1..], true),
+            _ => (expression, false),
+        };
+        let simple = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        match if simple { lookup(name) } else { None } {
+            Some(value) if segments => {
+                let value = if expression.starts_with('*') {
+                    value.trim_start_matches('/')
+                } else {
+                    &value
+                };
+                let encoded: Vec<_> = value.split('/').map(percent_encode).collect();
+                out.push_str(&encoded.join("/"));
+            }
+            Some(value) => out.push_str(&percent_encode(&value)),
+            None => {
+                match expression.strip_prefix('*') {
+                    Some(name) => out.push_str(&format!("{{+{}}}", name)),
+                    None => out.push_str(&rest[start..=end]),
+                }
+                templated = true;
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    (out, templated)
+}
+
+/// Expand the variables of `template` with the top-level scalar fields of
+/// `values`; see `expand_with`.
+pub fn expand(template: &str, values: &JsonValue) -> (String, bool) {
+    expand_with(template, |name| match values.get(name)? {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Number(n) => Some(n.to_string()),
+        JsonValue::Bool(b) => Some(b.to_string()),
+        _ => None,
+    })
+}
@@ -0,0 +1,109 @@
+//! Walking the links of a resource and its embedded resources.
+//!
+//! `HalResource::accept` and `HalResource::accept_mut` walk a resource depth
+//! first: the resource itself, then its links (curies included, under the
+//! `curies` relation), then each embedded resource in turn.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustic_hal::visitor::PrefixRewriter;
+//! use rustic_hal::HalResource;
+//!
+//! let mut gateway = PrefixRewriter::new()
+//!     .with_prefix("http://orders.internal:8080", "https://api.acme.com/orders");
+//!
+//! let mut res = HalResource::new(())
+//!     .with_link("self", "http://orders.internal:8080/12")
+//!     .with_curie("acme", "http://orders.internal:8080/docs/{rel}");
+//! res.accept_mut(&mut gateway);
+//!
+//! assert_eq!(res.get_self().unwrap().href, "https://api.acme.com/orders/12");
+//! assert_eq!(res.get_link("curies").unwrap().href, "https://api.acme.com/orders/docs/{rel}");
+//! ```
+use super::link::HalLink;
+use super::resource::HalResource;
+
+/// Read-only visitor over a resource tree.
+pub trait HalVisitor {
+    /// Called for every resource, before its links and embedded resources.
+    fn visit_resource(&mut self, _resource: &HalResource) {}
+
+    /// Called for every link with its relation.
+    fn visit_link(&mut self, _rel: &str, _link: &HalLink) {}
+}
+
+/// Mutating visitor over a resource tree.
+pub trait HalVisitorMut {
+    /// Called for every resource, before its links and embedded resources.
+    fn visit_resource_mut(&mut self, _resource: &mut HalResource) {}
+
+    /// Called for every link with its relation.
+    fn visit_link_mut(&mut self, _rel: &str, _link: &mut HalLink) {}
+}
+
+/// Adapts a closure over hrefs into a visitor, see `HalResource::rewrite_hrefs`.
+pub(crate) struct HrefRewriter<F>(pub F);
+
+impl<F> HalVisitorMut for HrefRewriter<F>
+where
+    F: FnMut(&str) -> String,
+{
+    fn visit_link_mut(&mut self, _rel: &str, link: &mut HalLink) {
+        link.href = (self.0)(&link.href);
+    }
+}
+
+/// Maps internal base URLs to public ones, for proxies and gateways.
+///
+/// A prefix only matches on a boundary: `http://orders.internal` rewrites
+/// `http://orders.internal/12` but not `http://orders.internal.old/12`.
+/// Prefixes are plain strings and never contain a template expression, so
+/// `{...}` expressions in templated hrefs are left as they are.
+#[derive(Clone, Debug, Default)]
+pub struct PrefixRewriter {
+    prefixes: Vec<(String, String)>,
+}
+
+impl PrefixRewriter {
+    pub fn new() -> PrefixRewriter {
+        PrefixRewriter::default()
+    }
+
+    /// Rewrite hrefs starting with `from` to start with `to` instead.
+    ///
+    /// When several prefixes match, the longest wins.
+    pub fn with_prefix<S, T>(mut self, from: S, to: T) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        let from = from.into();
+        let from = from.trim_end_matches('/').to_string();
+        let to = to.into().trim_end_matches('/').to_string();
+        self.prefixes.push((from, to));
+        self.prefixes.sort_by_key(|p| std::cmp::Reverse(p.0.len()));
+        self
+    }
+
+    /// The rewritten href, or `None` if no prefix matches.
+    pub fn rewrite(&self, href: &str) -> Option<String> {
+        self.prefixes.iter().find_map(|(from, to)| {
+            let rest = href.strip_prefix(from.as_str())?;
+            match rest.chars().next() {
+                None | Some('/') | Some('?') | Some('#') | Some('{') => {
+                    Some(format!("{}{}", to, rest))
+                }
+                _ => None,
+            }
+        })
+    }
+}
+
+impl HalVisitorMut for PrefixRewriter {
+    fn visit_link_mut(&mut self, _rel: &str, link: &mut HalLink) {
+        if let Some(href) = self.rewrite(&link.href) {
+            link.href = href;
+        }
+    }
+}